@@ -0,0 +1,323 @@
+use std::collections::HashSet;
+
+use proc_macro2::Span;
+use syn::{spanned::Spanned, Data, DeriveInput, Fields, Ident, LitStr, Path, Type};
+
+/// Options from `#[amo(...)]` attributes on the struct itself.
+pub struct Container {
+    pub rename_all: Option<RenameRule>,
+    pub policy: Option<Path>,
+}
+
+/// A named field and the options from its `#[amo(...)]` attributes.
+pub struct Field {
+    pub ident: Ident,
+    pub ty: Type,
+    pub rename: Option<String>,
+    pub skip: bool,
+    pub default: Option<DefaultValue>,
+    pub flatten: bool,
+    pub with: Option<Path>,
+    /// The primary key this field is part of, if any.
+    pub primary: Option<KeyKind>,
+    /// The secondary index keys this field is part of.
+    pub secondary: Vec<SecondaryKey>,
+    /// The indexes this non-key field is projected into.
+    pub include: Vec<LitStr>,
+}
+
+/// A field's part in the key of a secondary index.
+pub struct SecondaryKey {
+    pub index: LitStr,
+    pub kind: KeyKind,
+    pub local: bool,
+    pub projection: Option<(Projection, Span)>,
+}
+
+/// The projection set on an index's key, for indexes that don't list included fields.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    All,
+    KeysOnly,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    Hash,
+    Range,
+}
+
+pub enum DefaultValue {
+    Trait,
+    Path(Path),
+}
+
+impl Field {
+    /// The name of the DynamoDB attribute this field is stored in.
+    pub fn attribute(&self, container: &Container) -> String {
+        if let Some(rename) = &self.rename {
+            return rename.clone();
+        }
+        let name = self.ident.to_string();
+        let name = name.strip_prefix("r#").unwrap_or(&name);
+        match container.rename_all {
+            Some(rule) => rule.apply(name),
+            None => name.to_owned(),
+        }
+    }
+}
+
+pub fn parse(input: &DeriveInput) -> syn::Result<(Container, Vec<Field>)> {
+    let mut container = Container {
+        rename_all: None,
+        policy: None,
+    };
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("amo")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                let rule: LitStr = meta.value()?.parse()?;
+                container.rename_all = Some(RenameRule::parse(&rule)?);
+            } else if meta.path.is_ident("policy") {
+                container.policy = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unknown amo container attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(error(
+                    input.ident.span(),
+                    "amo derives require named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(error(
+                input.ident.span(),
+                "amo derives only support structs",
+            ))
+        }
+    };
+
+    let fields = fields
+        .iter()
+        .map(|f| {
+            let mut field = Field {
+                ident: f.ident.clone().expect("named fields have identifiers"),
+                ty: f.ty.clone(),
+                rename: None,
+                skip: false,
+                default: None,
+                flatten: false,
+                with: None,
+                primary: None,
+                secondary: Vec::new(),
+                include: Vec::new(),
+            };
+            for attr in f.attrs.iter().filter(|a| a.path().is_ident("amo")) {
+                let mut primary = false;
+                let mut secondary = false;
+                let mut index = None;
+                let mut local = false;
+                let mut projection = None;
+                let mut kind = None;
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        let rename: LitStr = meta.value()?.parse()?;
+                        field.rename = Some(rename.value());
+                    } else if meta.path.is_ident("skip") {
+                        field.skip = true;
+                    } else if meta.path.is_ident("default") {
+                        field.default = Some(if meta.input.peek(syn::Token![=]) {
+                            DefaultValue::Path(meta.value()?.parse()?)
+                        } else {
+                            DefaultValue::Trait
+                        });
+                    } else if meta.path.is_ident("flatten") {
+                        field.flatten = true;
+                    } else if meta.path.is_ident("with") {
+                        field.with = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("primary") {
+                        primary = true;
+                    } else if meta.path.is_ident("secondary") {
+                        secondary = true;
+                    } else if meta.path.is_ident("index") {
+                        index = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("local") {
+                        local = true;
+                    } else if meta.path.is_ident("projection") {
+                        let ident: Ident = meta.value()?.parse()?;
+                        let value = match ident.to_string().as_str() {
+                            "all" => Projection::All,
+                            "keys_only" => Projection::KeysOnly,
+                            _ => {
+                                return Err(syn::Error::new(
+                                    ident.span(),
+                                    "expected `all` or `keys_only`; list included fields with \
+                                     `#[amo(include = \"index\")]` on them",
+                                ))
+                            }
+                        };
+                        projection = Some((value, ident.span()));
+                    } else if meta.path.is_ident("include") {
+                        field.include.push(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("kind") {
+                        let ident: Ident = meta.value()?.parse()?;
+                        kind = Some(match ident.to_string().as_str() {
+                            "hash" => KeyKind::Hash,
+                            "range" => KeyKind::Range,
+                            _ => {
+                                return Err(syn::Error::new(
+                                    ident.span(),
+                                    "expected `hash` or `range`",
+                                ))
+                            }
+                        });
+                    } else {
+                        return Err(meta.error("unknown amo field attribute"));
+                    }
+                    Ok(())
+                })?;
+
+                if (index.is_some() || local || projection.is_some()) && !secondary {
+                    return Err(error(
+                        attr.span(),
+                        "`index`, `local` and `projection` are only valid on `secondary` keys",
+                    ));
+                }
+                match (primary, secondary, kind) {
+                    (true, true, _) => {
+                        return Err(error(
+                            attr.span(),
+                            "`primary` and `secondary` keys go in separate attributes",
+                        ))
+                    }
+                    (true, false, Some(kind)) => field.primary = Some(kind),
+                    (false, true, Some(kind)) => {
+                        let index = index.ok_or_else(|| {
+                            error(attr.span(), "`secondary` requires `index = \"name\"`")
+                        })?;
+                        field.secondary.push(SecondaryKey {
+                            index,
+                            kind,
+                            local,
+                            projection,
+                        });
+                    }
+                    (true, false, None) | (false, true, None) => {
+                        return Err(error(
+                            attr.span(),
+                            "keys require `kind = hash` or `kind = range`",
+                        ))
+                    }
+                    (false, false, Some(_)) => {
+                        return Err(error(
+                            attr.span(),
+                            "`kind` is only valid on `primary` and `secondary` keys",
+                        ))
+                    }
+                    (false, false, None) => {}
+                }
+            }
+
+            // Key types come from the field's own `Value` impl, which `with` would bypass.
+            if (field.primary.is_some() || !field.secondary.is_empty())
+                && (field.flatten || field.skip || field.with.is_some())
+            {
+                return Err(error(
+                    f.span(),
+                    "key fields can't be flattened, skipped or use `with`",
+                ));
+            }
+            if !field.include.is_empty() && (field.flatten || field.skip) {
+                return Err(error(
+                    f.span(),
+                    "flattened and skipped fields can't be included in an index",
+                ));
+            }
+            if field.flatten && (field.rename.is_some() || field.with.is_some() || field.skip) {
+                return Err(error(
+                    f.span(),
+                    "`flatten` can't be combined with `rename`, `with` or `skip`",
+                ));
+            }
+            Ok(field)
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let mut attributes = HashSet::new();
+    for field in fields.iter().filter(|f| !f.skip && !f.flatten) {
+        let attribute = field.attribute(&container);
+        if !attributes.insert(attribute.clone()) {
+            return Err(error(
+                field.ident.span(),
+                &format!("another field is already stored in the attribute `{attribute}`"),
+            ));
+        }
+    }
+
+    Ok((container, fields))
+}
+
+fn error(span: Span, message: &str) -> syn::Error {
+    syn::Error::new(span, message)
+}
+
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return Err(syn::Error::new(lit.span(), "unknown rename_all rule")),
+        })
+    }
+
+    /// Renames a snake_case field name.
+    fn apply(self, field: &str) -> String {
+        let capitalize = |word: &str| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        };
+        let words = field.split('_').filter(|w| !w.is_empty());
+
+        match self {
+            Self::Lower => field.to_lowercase(),
+            Self::Upper => field.to_uppercase(),
+            Self::Pascal => words.map(capitalize).collect(),
+            Self::Camel => words
+                .enumerate()
+                .map(|(i, w)| if i == 0 { w.to_owned() } else { capitalize(w) })
+                .collect(),
+            Self::Snake => field.to_owned(),
+            Self::ScreamingSnake => field.to_uppercase(),
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.replace('_', "-").to_uppercase(),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::DeriveInput;
+
+use crate::attr::{self, Container, DefaultValue, Field};
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let (container, fields) = attr::parse(&input)?;
+    let serialize = serialize(&input, &container, &fields);
+    let deserialize = deserialize(&input, &container, &fields);
+    Ok(quote! {
+        #serialize
+        #deserialize
+    })
+}
+
+fn policy(container: &Container) -> TokenStream {
+    match &container.policy {
+        Some(policy) => quote!(&#policy),
+        None => quote!(&::amo::value::Policy::DEFAULT),
+    }
+}
+
+fn serialize(input: &DeriveInput, container: &Container, fields: &[Field]) -> TokenStream {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let policy = policy(container);
+
+    let pushes = fields.iter().filter(|f| !f.skip).map(|field| {
+        let member = &field.ident;
+        let attribute = field.attribute(container);
+        if field.flatten {
+            quote! {
+                attributes.extend(::amo::item::Serialize::serialize(&self.#member));
+            }
+        } else if let Some(with) = &field.with {
+            quote! {
+                attributes.push(
+                    #with::serialize(&self.#member)
+                        .map(|value| (#attribute.to_owned(), value))
+                        .map_err(|e| e.at_field(#attribute)),
+                );
+            }
+        } else {
+            quote! {
+                match ::amo::value::Serialize::serialize_attribute(&self.#member, #policy) {
+                    ::core::result::Result::Ok(::core::option::Option::Some(value)) => {
+                        attributes.push(::core::result::Result::Ok((#attribute.to_owned(), value)));
+                    }
+                    ::core::result::Result::Ok(::core::option::Option::None) => {}
+                    ::core::result::Result::Err(e) => {
+                        attributes.push(::core::result::Result::Err(e.at_field(#attribute)));
+                    }
+                }
+            }
+        }
+    });
+
+    let policy_const = container
+        .policy
+        .as_ref()
+        .map(|policy| quote!(const POLICY: ::amo::value::Policy = #policy;));
+
+    quote! {
+        impl #impl_generics ::amo::item::Serialize for #ident #ty_generics #where_clause {
+            #policy_const
+
+            fn serialize(
+                &self,
+            ) -> impl ::core::iter::Iterator<
+                Item = ::core::result::Result<
+                    (::std::string::String, ::amo::__private::AttributeValue),
+                    ::amo::error::SerializeError,
+                >,
+            > {
+                let mut attributes = ::std::vec::Vec::new();
+                #(#pushes)*
+                attributes.into_iter()
+            }
+        }
+    }
+}
+
+fn deserialize(input: &DeriveInput, container: &Container, fields: &[Field]) -> TokenStream {
+    let ident = &input.ident;
+    let item_name = ident.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let policy = policy(container);
+
+    let local = |field: &Field| format_ident!("__field_{}", field.ident);
+
+    // Attributes are removed from the map as their fields claim them, so flattened fields, which
+    // take whatever's left over, go last.
+    let (flattened, direct): (Vec<_>, Vec<_>) = fields.iter().partition(|f| f.flatten);
+
+    let direct = direct.into_iter().map(|field| {
+        let local = local(field);
+        let ty = &field.ty;
+        let attribute = field.attribute(container);
+        let name = field.ident.to_string();
+        let name = name.strip_prefix("r#").unwrap_or(&name);
+
+        let default = match &field.default {
+            Some(DefaultValue::Trait) => quote!(<#ty as ::core::default::Default>::default()),
+            Some(DefaultValue::Path(path)) => quote!(#path()),
+            None if field.skip => quote!(<#ty as ::core::default::Default>::default()),
+            None => quote! {
+                return ::core::result::Result::Err(
+                    ::amo::error::DeserializeError::missing_required_field(#item_name, #name)
+                        .at_field(#attribute),
+                )
+            },
+        };
+
+        if field.skip {
+            quote!(let #local: #ty = #default;)
+        } else if let Some(with) = &field.with {
+            quote! {
+                let #local: #ty = match map.remove(#attribute) {
+                    ::core::option::Option::Some(value) => #with::deserialize(value)
+                        .map_err(|e| e.at_field(#attribute).in_item(#item_name))?,
+                    ::core::option::Option::None => #default,
+                };
+            }
+        } else {
+            quote! {
+                let #local: #ty = match <#ty as ::amo::value::Deserialize>::deserialize_attribute(
+                    map.remove(#attribute),
+                    #policy,
+                )
+                .map_err(|e| e.at_field(#attribute).in_item(#item_name))? {
+                    ::core::option::Option::Some(value) => value,
+                    ::core::option::Option::None => #default,
+                };
+            }
+        }
+    });
+
+    let last = flattened.len().saturating_sub(1);
+    let flattened = flattened.iter().enumerate().map(|(i, field)| {
+        let local = local(field);
+        let ty = &field.ty;
+        let map = if i == last {
+            quote!(map)
+        } else {
+            quote!(map.clone())
+        };
+        quote! {
+            let #local: #ty = <#ty as ::amo::item::Deserialize>::deserialize_owned_from_map(#map)
+                .map_err(|e| e.in_item(#item_name))?;
+        }
+    });
+
+    let attributes = fields
+        .iter()
+        .filter(|f| !f.skip && !f.flatten)
+        .map(|f| f.attribute(container));
+    let flattened_attributes = fields.iter().filter(|f| f.flatten).map(|field| {
+        let ty = &field.ty;
+        quote! {
+            attributes.extend(<#ty as ::amo::item::Deserialize>::attributes()?);
+        }
+    });
+
+    let members = fields.iter().map(|field| {
+        let member = &field.ident;
+        let local = local(field);
+        quote!(#member: #local)
+    });
+
+    quote! {
+        impl #impl_generics ::amo::item::Deserialize for #ident #ty_generics #where_clause {
+            #[allow(unused_mut)]
+            fn deserialize_owned_from_map(
+                mut map: ::std::collections::HashMap<::std::string::String, ::amo::__private::AttributeValue>,
+            ) -> ::core::result::Result<Self, ::amo::error::DeserializeError> {
+                #(#direct)*
+                #(#flattened)*
+                ::core::result::Result::Ok(Self { #(#members),* })
+            }
+
+            fn attributes() -> ::core::option::Option<::std::vec::Vec<&'static str>> {
+                #[allow(unused_mut)]
+                let mut attributes = ::std::vec![#(#attributes),*];
+                #(#flattened_attributes)*
+                ::core::option::Option::Some(attributes)
+            }
+        }
+    }
+}
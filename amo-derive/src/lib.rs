@@ -0,0 +1,73 @@
+//! Derive macros for [amo](https://docs.rs/amo). These are re-exported from the `amo` crate and
+//! should be used from there.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod attr;
+mod item;
+mod table;
+
+/// Derives `amo::item::Serialize` and `amo::item::Deserialize` for a struct with named fields.
+///
+/// Each field is stored in an attribute of the same name, using the field type's
+/// `amo::value::Serialize` and `amo::value::Deserialize` impls. Two fields can't be stored in the
+/// same attribute.
+///
+/// Container attributes:
+/// - `#[amo(rename_all = "...")]` renames every field: `"camelCase"`, `"PascalCase"`,
+///   `"snake_case"`, `"SCREAMING_SNAKE_CASE"`, `"kebab-case"`, `"SCREAMING-KEBAB-CASE"`,
+///   `"lowercase"` or `"UPPERCASE"`.
+/// - `#[amo(policy = PATH)]` uses the `amo::value::Policy` constant at `PATH` for every field
+///   instead of `Policy::DEFAULT`, and as the item's `Serialize::POLICY`, which updates use.
+///
+/// Field attributes:
+/// - `#[amo(rename = "name")]` stores the field in the attribute `name`.
+/// - `#[amo(skip)]` never writes the field, and reads it with `Default::default()`.
+/// - `#[amo(default)]` or `#[amo(default = path::to::fn)]` fills in the field when its attribute
+///   is absent rather than failing.
+/// - `#[amo(flatten)]` merges the attributes of a field whose type also implements the item
+///   traits into this item.
+/// - `#[amo(with = module)]` uses `module::serialize(&T) -> Result<AttributeValue, SerializeError>`
+///   and `module::deserialize(AttributeValue) -> Result<T, DeserializeError>` for the field.
+#[proc_macro_derive(Item, attributes(amo))]
+pub fn derive_item(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    item::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives a table handle for an item type, named after the item with a `Table` suffix.
+///
+/// One field must be marked `#[amo(primary, kind = hash)]`, and at most one
+/// `#[amo(primary, kind = range)]`. The generated handle implements `amo::table::Table` and either
+/// `amo::table::HashTable` or `amo::table::HashRangeTable`, using the key fields' attribute names
+/// (after any `rename` or `rename_all`) and value types. It also gets a `new(client, name)`
+/// constructor, and `get`, `update` and `delete` methods taking the key fields by their Rust types.
+/// Those return a `SerializeError` straight away if a key is empty or too long. Key fields can't be
+/// skipped, flattened or use `with`.
+///
+/// A field marked `#[amo(secondary, index = "name", kind = hash)]` or `kind = range` is part of
+/// the key of the secondary index `name`; a field can be in several indexes, with one attribute
+/// each. Global indexes need a hash key field. Local indexes are marked `local` on their range key
+/// field and share the table's hash key. Each index gets a marker type implementing
+/// `amo::table::Index`, named after the item and the index: `by-account` on `Tag` is
+/// `TagByAccount`.
+///
+/// Indexes project every attribute unless one of their key fields sets `projection = keys_only`,
+/// or other fields are marked `#[amo(include = "name")]` to project just those besides the keys.
+/// Reads of such an index fail before sending if their item type reads an attribute the index
+/// doesn't hold, so read them with `project` and a type holding only the projected fields.
+///
+/// If the table has a range key or any indexes, its `query()` method returns a `{Item}TableQuery`
+/// builder, with a `by_{hash field}` method for querying the table when it has a range key, and one
+/// method per index named after it in snake case, such as `by_account`. Each takes the hash key by
+/// its Rust type.
+#[proc_macro_derive(Table, attributes(amo))]
+pub fn derive_table(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    table::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,450 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, DeriveInput, Ident, LitStr};
+
+use crate::attr::{self, Container, Field, KeyKind, Projection};
+
+/// A secondary index, the fields in its key and the fields projected into it.
+struct Index<'a> {
+    name: LitStr,
+    local: bool,
+    hash: Option<&'a Field>,
+    range: Option<&'a Field>,
+    projection: Option<Projection>,
+    include: Vec<&'a Field>,
+}
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "#[derive(Table)] doesn't support generic items",
+        ));
+    }
+
+    let (container, fields) = attr::parse(&input)?;
+    let key = |kind| {
+        let mut keys = fields.iter().filter(move |f| f.primary == Some(kind));
+        let key = keys.next();
+        match keys.next() {
+            Some(duplicate) => Err(syn::Error::new(
+                duplicate.ident.span(),
+                "a table can only have one primary key of each kind",
+            )),
+            None => Ok(key),
+        }
+    };
+    let hash = key(KeyKind::Hash)?.ok_or_else(|| {
+        syn::Error::new(
+            input.ident.span(),
+            "#[derive(Table)] requires a field marked #[amo(primary, kind = hash)]",
+        )
+    })?;
+    let range = key(KeyKind::Range)?;
+
+    let vis = &input.vis;
+    let item = &input.ident;
+    let table = format_ident!("{}Table", item);
+    let table_doc = format!("A handle to a DynamoDB table of [`{item}`] items.");
+
+    let key_impl = |field: &Field, kind: &str| key_impl(field, kind, &container);
+
+    // Building a single-item request fails if the key does.
+    let returns = |operation: &str| {
+        let operation = format_ident!("{operation}");
+        quote! {
+            ::core::result::Result<
+                ::amo::operation::#operation<Self>,
+                ::amo::error::SerializeError,
+            >
+        }
+    };
+    let (get, update, delete) = (
+        returns("GetItem"),
+        returns("UpdateItem"),
+        returns("DeleteItem"),
+    );
+
+    let hash_impl = key_impl(hash, "Hash");
+    let (hash_param, hash_arg) = key_param(hash);
+    let (key_trait, key_impls, key_methods) = match range {
+        Some(range) => {
+            let range_impl = key_impl(range, "Range");
+            let (range_param, range_arg) = key_param(range);
+            (
+                quote!(::amo::table::HashRangeTable),
+                quote!(#hash_impl #range_impl),
+                quote! {
+                    pub fn get(&self, #hash_param, #range_param) -> #get {
+                        ::amo::table::HashRangeTable::get_raw(self, #hash_arg, #range_arg)
+                    }
+
+                    pub fn update(&self, #hash_param, #range_param) -> #update {
+                        ::amo::table::HashRangeTable::update_raw(self, #hash_arg, #range_arg)
+                    }
+
+                    pub fn delete(&self, #hash_param, #range_param) -> #delete {
+                        ::amo::table::HashRangeTable::delete_raw(self, #hash_arg, #range_arg)
+                    }
+                },
+            )
+        }
+        None => (
+            quote!(::amo::table::HashTable),
+            hash_impl,
+            quote! {
+                pub fn get(&self, #hash_param) -> #get {
+                    ::amo::table::HashTable::get_raw(self, #hash_arg)
+                }
+
+                pub fn update(&self, #hash_param) -> #update {
+                    ::amo::table::HashTable::update_raw(self, #hash_arg)
+                }
+
+                pub fn delete(&self, #hash_param) -> #delete {
+                    ::amo::table::HashTable::delete_raw(self, #hash_arg)
+                }
+            },
+        ),
+    };
+
+    let indexes = indexes(&fields, range.is_some())?;
+    let mut index_items = Vec::new();
+    let mut queries = Vec::new();
+    let mut query_names = Vec::new();
+    if range.is_some() {
+        let method = format_ident!("by_{}", hash.ident.to_string().trim_start_matches("r#"));
+        let doc = format!(
+            "Reads the items with the given `{}`, in range key order.",
+            hash.ident
+        );
+        query_names.push((method.to_string(), hash.ident.span()));
+        queries.push(quote! {
+            #[doc = #doc]
+            #vis fn #method(&self, #hash_param) -> ::amo::operation::Query<#table> {
+                ::amo::table::HashRangeTable::query_raw(self.0, #hash_arg)
+            }
+        });
+    }
+    for index in &indexes {
+        let words = words(&index.name)?;
+        let marker = format_ident!(
+            "{item}{}",
+            words.iter().map(|w| capitalize(w)).collect::<String>()
+        );
+        let method = format_ident!("{}", words.join("_"));
+        let name = &index.name;
+        let kind = if index.local {
+            quote!(::amo::table::Local)
+        } else {
+            quote!(::amo::table::Global)
+        };
+        // A local index's hash key is its table's.
+        let index_hash = index.hash.unwrap_or(hash);
+        let hash_impl = key_impl(index_hash, "Hash");
+        let range_impl = index.range.map(|range| {
+            let range_impl = key_impl(range, "Range");
+            quote! {
+                impl ::amo::table::HashRangeIndex for #marker {
+                    #range_impl
+                }
+            }
+        });
+        let mut keys = Vec::new();
+        for field in [Some(hash), range, Some(index_hash), index.range]
+            .into_iter()
+            .flatten()
+        {
+            let attribute = field.attribute(&container);
+            if !keys.contains(&attribute) {
+                keys.push(attribute);
+            }
+        }
+        let include = index.include.iter().map(|f| f.attribute(&container));
+        let projection = match index.projection {
+            _ if !index.include.is_empty() => quote! {
+                ::amo::table::IndexProjection::Include {
+                    keys: &[#(#keys),*],
+                    attributes: &[#(#include),*],
+                }
+            },
+            Some(Projection::KeysOnly) => quote! {
+                ::amo::table::IndexProjection::KeysOnly { keys: &[#(#keys),*] }
+            },
+            Some(Projection::All) | None => quote!(::amo::table::IndexProjection::All),
+        };
+        let marker_doc = format!(
+            "The `{}` index of [`{table}`], read through [`{table}::index`](::amo::table::Table::index) or [`{table}::query`].",
+            name.value()
+        );
+        index_items.push(quote! {
+            #[doc = #marker_doc]
+            #vis struct #marker(());
+
+            impl ::amo::table::Index for #marker {
+                type Table = #table;
+                type Item = #item;
+                type Kind = #kind;
+                const NAME: &'static str = #name;
+                const PROJECTION: ::amo::table::IndexProjection = #projection;
+            }
+
+            impl ::amo::table::HashIndex for #marker {
+                #hash_impl
+            }
+
+            #range_impl
+        });
+
+        let (param, arg) = key_param(index_hash);
+        let doc = format!(
+            "Reads the items in the `{}` index with the given `{}`.",
+            name.value(),
+            index_hash.ident
+        );
+        if let Some((_, span)) = query_names.iter().find(|(n, _)| method == n) {
+            let mut error = syn::Error::new(
+                name.span(),
+                format!("this index's query method `{method}` is already taken"),
+            );
+            error.combine(syn::Error::new(*span, "by this key"));
+            return Err(error);
+        }
+        query_names.push((method.to_string(), name.span()));
+        queries.push(quote! {
+            #[doc = #doc]
+            #vis fn #method(&self, #param) -> ::amo::operation::Query<::amo::table::IndexHandle<#marker>> {
+                ::amo::table::Table::index::<#marker>(self.0).query(#arg)
+            }
+        });
+    }
+
+    let query = if queries.is_empty() {
+        None
+    } else {
+        let query = format_ident!("{}Query", table);
+        let query_doc = format!(
+            "Builds queries of [`{table}`] and its indexes. Returned by [`{table}::query`]."
+        );
+        Some((
+            quote! {
+                /// Queries the table or one of its secondary indexes.
+                #vis fn query(&self) -> #query<'_> {
+                    #query(self)
+                }
+            },
+            quote! {
+                #[doc = #query_doc]
+                #[derive(Debug, Clone, Copy)]
+                #vis struct #query<'a>(&'a #table);
+
+                impl #query<'_> {
+                    #(#queries)*
+                }
+            },
+        ))
+    };
+    let (query_method, query_items) = query.unzip();
+
+    Ok(quote! {
+        #[doc = #table_doc]
+        #[derive(Debug, Clone)]
+        #vis struct #table {
+            name: ::std::sync::Arc<::std::string::String>,
+            client: ::amo::__private::Client,
+        }
+
+        impl #table {
+            pub fn new(client: ::amo::__private::Client, name: impl ::core::convert::Into<::std::string::String>) -> Self {
+                Self {
+                    name: ::std::sync::Arc::new(name.into()),
+                    client,
+                }
+            }
+
+            #key_methods
+
+            #query_method
+        }
+
+        impl ::amo::table::Table for #table {
+            type Item = #item;
+
+            fn name(&self) -> &str {
+                &self.name
+            }
+
+            fn client(&self) -> ::amo::__private::Client {
+                self.client.clone()
+            }
+        }
+
+        impl #key_trait for #table {
+            #key_impls
+        }
+
+        #query_items
+
+        #(#index_items)*
+    })
+}
+
+fn key_impl(field: &Field, kind: &str, container: &Container) -> TokenStream {
+    let ty = &field.ty;
+    let attribute = field.attribute(container);
+    let key_type = format_ident!("{kind}KeyType");
+    let key_attribute = format_ident!("{}_KEY_ATTRIBUTE", kind.to_uppercase());
+    quote! {
+        type #key_type = <#ty as ::amo::value::Value>::Type;
+        const #key_attribute: &'static str = #attribute;
+    }
+}
+
+/// A parameter taking a key field by anything that converts into its type, and the argument
+/// converting it.
+fn key_param(field: &Field) -> (TokenStream, TokenStream) {
+    let ident = &field.ident;
+    let ty = &field.ty;
+    (
+        quote!(#ident: impl ::core::convert::Into<#ty>),
+        quote!(::core::convert::Into::<#ty>::into(#ident)),
+    )
+}
+
+/// Groups the fields' secondary keys by index, in the order the indexes first appear.
+fn indexes(fields: &[Field], has_range: bool) -> syn::Result<Vec<Index<'_>>> {
+    let mut indexes: Vec<Index> = Vec::new();
+    for field in fields {
+        for key in &field.secondary {
+            let position = indexes
+                .iter()
+                .position(|i| i.name.value() == key.index.value());
+            let index = match position {
+                Some(position) => &mut indexes[position],
+                None => {
+                    indexes.push(Index {
+                        name: key.index.clone(),
+                        local: false,
+                        hash: None,
+                        range: None,
+                        projection: None,
+                        include: Vec::new(),
+                    });
+                    indexes.last_mut().expect("an index was just added")
+                }
+            };
+            index.local |= key.local;
+            if let Some((projection, span)) = key.projection {
+                if index
+                    .projection
+                    .replace(projection)
+                    .is_some_and(|p| p != projection)
+                {
+                    return Err(syn::Error::new(
+                        span,
+                        "this index's keys set different projections",
+                    ));
+                }
+            }
+            let slot = match key.kind {
+                KeyKind::Hash => &mut index.hash,
+                KeyKind::Range => &mut index.range,
+            };
+            if slot.replace(field).is_some() {
+                return Err(syn::Error::new(
+                    key.index.span(),
+                    "an index can only have one key of each kind",
+                ));
+            }
+        }
+    }
+
+    for field in fields {
+        for name in &field.include {
+            let index = indexes
+                .iter_mut()
+                .find(|i| i.name.value() == name.value())
+                .ok_or_else(|| syn::Error::new(name.span(), "no index has this name"))?;
+            let is_key = field.primary.is_some()
+                || field
+                    .secondary
+                    .iter()
+                    .any(|k| k.index.value() == name.value());
+            if is_key {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "key attributes are always projected, so don't include them",
+                ));
+            }
+            if index.projection.is_some() {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "indexes with included fields can't also set `projection`",
+                ));
+            }
+            index.include.push(field);
+        }
+    }
+
+    for index in &indexes {
+        let span = index.name.span();
+        if index.local {
+            if index.hash.is_some() {
+                return Err(syn::Error::new(
+                    span,
+                    "local indexes share the table's hash key, so only mark their range key",
+                ));
+            }
+            if !has_range {
+                return Err(syn::Error::new(
+                    span,
+                    "local indexes are only allowed on tables with a range key",
+                ));
+            }
+        } else if index.hash.is_none() {
+            return Err(syn::Error::new(
+                span,
+                "global indexes need a field marked `kind = hash`",
+            ));
+        }
+    }
+    Ok(indexes)
+}
+
+/// Splits an index name into lowercase words, at punctuation and where capitals start, for naming
+/// its marker type and query method.
+fn words(name: &LitStr) -> syn::Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous = ' ';
+    for c in name.value().chars() {
+        let capital = c.is_ascii_uppercase() && !previous.is_ascii_uppercase();
+        if !c.is_ascii_alphanumeric() || capital {
+            words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+        }
+        if c.is_ascii_alphanumeric() {
+            word.push(c.to_ascii_lowercase());
+        }
+        previous = c;
+    }
+    words.extend((!word.is_empty()).then_some(word));
+
+    let valid = words
+        .first()
+        .is_some_and(|w| w.starts_with(|c: char| c.is_ascii_alphabetic()));
+    if !valid || syn::parse_str::<Ident>(&words.join("_")).is_err() {
+        return Err(syn::Error::new(
+            name.span(),
+            "index names must start with a letter and not be a keyword, to name their marker type and query method",
+        ));
+    }
+    Ok(words)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
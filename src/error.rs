@@ -1,51 +1,750 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::sync::Arc;
 
-use aws_sdk_dynamodb::{error::SdkError, operation::get_item::GetItemError, types::AttributeValue};
+use aws_sdk_dynamodb::{
+    config::http::HttpResponse,
+    error::{ProvideErrorMetadata, SdkError},
+    operation::{
+        batch_get_item::BatchGetItemError, batch_write_item::BatchWriteItemError,
+        delete_item::DeleteItemError, get_item::GetItemError, put_item::PutItemError,
+        query::QueryError, scan::ScanError, transact_get_items::TransactGetItemsError,
+        transact_write_items::TransactWriteItemsError, update_item::UpdateItemError, RequestId,
+    },
+    types::{AttributeValue, CancellationReason},
+};
 
+use crate::item;
 
+/// Returned when an attribute value can't be converted to a Rust value.
+///
+/// The error records where in the item the failure happened and which item type was being read,
+/// but never the attribute's contents, so it's safe to log.
 #[derive(Debug, Clone)]
-pub struct DeserializeError;
+pub struct DeserializeError {
+    item_type: Option<Cow<'static, str>>,
+    path: AttributePath,
+    kind: DeserializeErrorKind,
+}
+
+/// What went wrong while deserializing an attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeserializeErrorKind {
+    /// A required attribute was absent. The attribute is the last segment of the error's path.
+    MissingAttribute,
+    /// A required field of an item had no attribute. The attribute, when known, is the last
+    /// segment of the error's path.
+    MissingField {
+        /// The name of the Rust field.
+        field: String,
+    },
+    /// The attribute had a different DynamoDB type than the Rust type reads.
+    UnexpectedType {
+        /// The [`Type::NAME`](crate::value::Type::NAME) the Rust type expects.
+        expected: &'static str,
+        /// The type descriptor of the attribute that was found, e.g. `"N"`.
+        actual: &'static str,
+    },
+    /// The attribute had the right type, but its value was rejected.
+    Invalid(String),
+}
 
 impl DeserializeError {
+    /// The Rust field `field` of `item_type` is required but had no attribute. Chain
+    /// [`at_field`](Self::at_field) to record the attribute it's stored in.
     pub fn missing_required_field(item_type: &str, field: &str) -> Self {
-        Self
+        Self {
+            item_type: Some(Cow::Owned(item_type.to_owned())),
+            path: AttributePath::default(),
+            kind: DeserializeErrorKind::MissingField {
+                field: field.to_owned(),
+            },
+        }
+    }
+
+    pub fn unexpected_value_type(expected: &'static str, actual: AttributeValue) -> Self {
+        Self::new(DeserializeErrorKind::UnexpectedType {
+            expected,
+            actual: attribute_kind(&actual),
+        })
     }
-    
-    pub fn unexpected_value_type(expected: &str, actual: AttributeValue) -> Self {
-        todo!()
+
+    pub(crate) fn missing_attribute(name: &str) -> DeserializeError {
+        Self::new(DeserializeErrorKind::MissingAttribute).at_field(name)
     }
-    
+
     pub(crate) fn invalid(e: impl Display) -> DeserializeError {
-        todo!()
+        Self::new(DeserializeErrorKind::Invalid(e.to_string()))
+    }
+
+    fn new(kind: DeserializeErrorKind) -> Self {
+        Self {
+            item_type: None,
+            path: AttributePath::default(),
+            kind,
+        }
+    }
+
+    /// Records that this error occurred in the map entry or item attribute `name`.
+    pub fn at_field(mut self, name: &str) -> Self {
+        self.path
+            .segments
+            .insert(0, PathSegment::Field(name.to_owned()));
+        self
+    }
+
+    /// Records that this error occurred in the list element at `index`.
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.path.segments.insert(0, PathSegment::Index(index));
+        self
+    }
+
+    /// Records that this error occurred while reading an item of type `item_type`. Outer items
+    /// replace inner ones, so the path is always relative to the reported item type.
+    pub fn in_item(mut self, item_type: &'static str) -> Self {
+        self.item_type = Some(Cow::Borrowed(item_type));
+        self
+    }
+
+    /// The name of the item type being read, if the error came from an item.
+    pub fn item_type(&self) -> Option<&str> {
+        self.item_type.as_deref()
+    }
+
+    /// The path from the item, or the top-level value, to the attribute that failed.
+    pub fn path(&self) -> &AttributePath {
+        &self.path
     }
+
+    pub fn kind(&self) -> &DeserializeErrorKind {
+        &self.kind
+    }
+}
+
+impl Display for DeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(item_type) = &self.item_type {
+            write!(f, "invalid {item_type}")?;
+        }
+        if !self.path.is_empty() {
+            if self.item_type.is_some() {
+                f.write_str(" ")?;
+            }
+            write!(f, "at {}", self.path)?;
+        }
+        if self.item_type.is_some() || !self.path.is_empty() {
+            f.write_str(": ")?;
+        }
+        Display::fmt(&self.kind, f)
+    }
+}
+
+impl Display for DeserializeErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingAttribute => f.write_str("missing required attribute"),
+            Self::MissingField { field } => write!(f, "missing required field `{field}`"),
+            Self::UnexpectedType { expected, actual } => {
+                write!(f, "expected {expected}, found {actual}")
+            }
+            Self::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+/// A path to a nested attribute, such as `tags.labels[3].name`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttributePath {
+    segments: Vec<PathSegment>,
 }
 
+/// One step of an [`AttributePath`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// An item attribute or map entry.
+    Field(String),
+    /// A list element.
+    Index(usize),
+}
+
+impl AttributePath {
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
 
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl Display for AttributePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) if i == 0 => f.write_str(name)?,
+                PathSegment::Field(name) => write!(f, ".{name}")?,
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The DynamoDB type descriptor of `value`, without its payload.
+pub(crate) fn attribute_kind(value: &AttributeValue) -> &'static str {
+    match value {
+        AttributeValue::S(_) => "S",
+        AttributeValue::N(_) => "N",
+        AttributeValue::B(_) => "B",
+        AttributeValue::Bool(_) => "BOOL",
+        AttributeValue::Null(_) => "NULL",
+        AttributeValue::M(_) => "M",
+        AttributeValue::L(_) => "L",
+        AttributeValue::Ss(_) => "SS",
+        AttributeValue::Ns(_) => "NS",
+        AttributeValue::Bs(_) => "BS",
+        _ => "<unknown>",
+    }
+}
+
+/// Returned when a Rust value can't be converted to an attribute value, or the result breaks one
+/// of DynamoDB's limits.
 #[derive(Debug, Clone)]
-pub struct SerializeError;
+pub struct SerializeError {
+    path: AttributePath,
+    kind: SerializeErrorKind,
+}
+
+/// What went wrong while serializing an attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SerializeErrorKind {
+    /// The value can't be represented as a DynamoDB number, e.g. because it's NaN.
+    InvalidNumber(NumberError),
+    /// DynamoDB doesn't store empty sets.
+    EmptySet,
+    /// Key attributes can't be empty strings or binaries.
+    EmptyKey,
+    /// The value was rejected for some other reason, such as a `None` where a value is required.
+    Invalid(String),
+    /// Returned by user code through [`SerializeError::custom`].
+    Custom(String),
+    /// The serialized item is larger than DynamoDB's item size limit.
+    ItemTooLarge {
+        /// The item's size in bytes.
+        size: usize,
+        /// The largest size DynamoDB allows.
+        limit: usize,
+    },
+    /// The serialized key attribute is longer than DynamoDB allows for its kind of key.
+    KeyTooLong {
+        /// The key's length in bytes.
+        size: usize,
+        /// The longest key DynamoDB allows.
+        limit: usize,
+    },
+}
 
 impl SerializeError {
+    /// An error with a custom message, for use in `with` modules and hand-written `Serialize`
+    /// impls.
+    pub fn custom(e: impl Display) -> Self {
+        Self::new(SerializeErrorKind::Custom(e.to_string()))
+    }
+
+    pub(crate) fn invalid(e: impl Display) -> SerializeError {
+        Self::new(SerializeErrorKind::Invalid(e.to_string()))
+    }
+
+    pub(crate) fn new(kind: SerializeErrorKind) -> Self {
+        Self {
+            path: AttributePath::default(),
+            kind,
+        }
+    }
+
+    /// Records that this error occurred in the map entry or item attribute `name`.
+    pub fn at_field(mut self, name: &str) -> Self {
+        self.path
+            .segments
+            .insert(0, PathSegment::Field(name.to_owned()));
+        self
+    }
+
+    /// Records that this error occurred in the list element at `index`.
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.path.segments.insert(0, PathSegment::Index(index));
+        self
+    }
+
+    /// The path from the item, or the top-level value, to the attribute that failed. Key errors
+    /// name the key attribute.
+    pub fn path(&self) -> &AttributePath {
+        &self.path
+    }
+
+    pub fn kind(&self) -> &SerializeErrorKind {
+        &self.kind
+    }
+}
+
+impl From<NumberError> for SerializeError {
+    fn from(value: NumberError) -> Self {
+        Self::new(SerializeErrorKind::InvalidNumber(value))
+    }
+}
+
+impl Display for SerializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "at {}: ", self.path)?;
+        }
+        Display::fmt(&self.kind, f)
+    }
 }
 
+impl Display for SerializeErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidNumber(e) => Display::fmt(e, f),
+            Self::EmptySet => f.write_str("DynamoDB does not allow empty sets"),
+            Self::EmptyKey => f.write_str("key attributes can't be empty"),
+            Self::Invalid(message) | Self::Custom(message) => f.write_str(message),
+            Self::ItemTooLarge { size, limit } => {
+                write!(f, "item is {size} bytes, more than the limit of {limit}")
+            }
+            Self::KeyTooLong { size, limit } => {
+                write!(f, "key is {size} bytes, more than the limit of {limit}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SerializeError {}
 
+/// Returned when a value is rejected by the validation hook of a [`value_type!`](crate::value_type)
+/// newtype.
 #[derive(Debug, Clone)]
-pub struct ReadError;
+pub struct ValidationError {
+    type_name: &'static str,
+    message: String,
+}
 
-impl<R> From<SdkError<GetItemError, R>> for ReadError {
-    fn from(value: SdkError<GetItemError, R>) -> Self {
-        todo!()
+impl ValidationError {
+    pub fn new(type_name: &'static str, e: impl Display) -> Self {
+        Self {
+            type_name,
+            message: e.to_string(),
+        }
     }
 }
 
-impl From<DeserializeError> for ReadError {
-    fn from(value: DeserializeError) -> Self {
-        todo!()
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid {}: {}", self.type_name, self.message)
     }
 }
 
-impl From<SerializeError> for ReadError {
-    fn from(value: SerializeError) -> Self {
-        todo!()
+impl std::error::Error for ValidationError {}
+
+impl From<ValidationError> for DeserializeError {
+    fn from(value: ValidationError) -> Self {
+        DeserializeError::invalid(value)
     }
 }
 
+/// Returned when a string or Rust number can't be represented as a DynamoDB number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberError {
+    kind: NumberErrorKind,
+}
+
+/// Why a value isn't a valid DynamoDB number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum NumberErrorKind {
+    /// The string isn't a decimal number.
+    Syntax,
+    /// The number has more than 38 significant digits.
+    Precision,
+    /// The number's magnitude is below 1E-130 or at least 1E+126.
+    Range,
+    /// The number is NaN or infinite.
+    NotFinite,
+}
+
+impl NumberError {
+    pub(crate) fn syntax() -> Self {
+        Self {
+            kind: NumberErrorKind::Syntax,
+        }
+    }
+
+    pub(crate) fn precision() -> Self {
+        Self {
+            kind: NumberErrorKind::Precision,
+        }
+    }
+
+    pub(crate) fn range() -> Self {
+        Self {
+            kind: NumberErrorKind::Range,
+        }
+    }
+
+    pub(crate) fn not_finite() -> Self {
+        Self {
+            kind: NumberErrorKind::NotFinite,
+        }
+    }
+
+    pub fn kind(&self) -> NumberErrorKind {
+        self.kind
+    }
+}
+
+impl Display for NumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self.kind {
+            NumberErrorKind::Syntax => "value is not a valid number",
+            NumberErrorKind::Precision => "number has more than 38 significant digits",
+            NumberErrorKind::Range => "number magnitude is outside the range DynamoDB supports",
+            NumberErrorKind::NotFinite => "NaN and infinite numbers are not supported",
+        })
+    }
+}
+
+impl std::error::Error for NumberError {}
+
+/// A request to DynamoDB that failed, whether DynamoDB rejected it or it never got a response.
+///
+/// The underlying SDK error is available through [`Error::source`](std::error::Error::source).
+/// Clones share it.
+#[derive(Debug, Clone)]
+pub struct ServiceError {
+    class: ErrorClass,
+    code: Option<String>,
+    message: Option<String>,
+    request_id: Option<String>,
+    retryable: bool,
+    current_item: Option<HashMap<String, AttributeValue>>,
+    source: Arc<dyn std::error::Error + Send + Sync>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorClass {
+    Throttled,
+    ConditionalCheckFailed,
+    ResourceNotFound,
+    Validation,
+    TransactionConflict,
+    Service,
+    Network,
+}
+
+impl ServiceError {
+    fn new<E>(error: SdkError<E, HttpResponse>, class: Option<ErrorClass>) -> Self
+    where
+        E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+    {
+        let code = error.code().map(str::to_owned);
+        let status = error.raw_response().map(|r| r.status().as_u16());
+        let class = class.unwrap_or(match &error {
+            SdkError::ConstructionFailure(_) => ErrorClass::Validation,
+            SdkError::TimeoutError(_)
+            | SdkError::DispatchFailure(_)
+            | SdkError::ResponseError(_) => ErrorClass::Network,
+            _ => match code.as_deref() {
+                Some(
+                    "ProvisionedThroughputExceededException"
+                    | "RequestLimitExceeded"
+                    | "ThrottlingException",
+                ) => ErrorClass::Throttled,
+                Some("ConditionalCheckFailedException") => ErrorClass::ConditionalCheckFailed,
+                Some("ResourceNotFoundException") => ErrorClass::ResourceNotFound,
+                Some("ValidationException") => ErrorClass::Validation,
+                Some("TransactionConflictException" | "TransactionInProgressException") => {
+                    ErrorClass::TransactionConflict
+                }
+                _ => ErrorClass::Service,
+            },
+        });
+        let retryable = match class {
+            ErrorClass::Throttled | ErrorClass::TransactionConflict | ErrorClass::Network => true,
+            ErrorClass::Service => {
+                matches!(
+                    code.as_deref(),
+                    Some("InternalServerError" | "ServiceUnavailable")
+                ) || status.is_some_and(|s| s >= 500)
+            }
+            _ => false,
+        };
+
+        Self {
+            class,
+            message: error.message().map(str::to_owned),
+            request_id: error.request_id().map(str::to_owned),
+            code,
+            retryable,
+            current_item: None,
+            source: Arc::new(error),
+        }
+    }
+
+    fn with_current_item(mut self, item: Option<HashMap<String, AttributeValue>>) -> Self {
+        self.current_item = item;
+        self
+    }
+
+    /// The error code DynamoDB returned, such as `ValidationException`, if it returned one.
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// The error message DynamoDB returned, if it returned one.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// The ID DynamoDB assigned to the request, if it got that far.
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// Whether sending the same request again might succeed.
+    pub fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+
+    /// The item as it was when a write's condition failed, read as `I`. DynamoDB only returns it
+    /// for put, update and delete requests with a condition.
+    pub fn current_item<I: item::Deserialize>(&self) -> Option<Result<I, DeserializeError>> {
+        self.current_item.clone().map(I::deserialize_owned_from_map)
+    }
+
+    /// The item as it was when a write's condition failed, as DynamoDB returned it.
+    pub fn current_item_raw(&self) -> Option<&HashMap<String, AttributeValue>> {
+        self.current_item.as_ref()
+    }
+}
+
+impl Display for ServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.code, &self.message) {
+            (Some(code), Some(message)) => write!(f, "{code}: {message}"),
+            (Some(code), None) => f.write_str(code),
+            (None, _) => Display::fmt(&self.source, f),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+/// Classifies a canceled transaction by the first reason that explains it.
+fn cancellation_class(reasons: &[CancellationReason]) -> Option<ErrorClass> {
+    reasons.iter().find_map(|reason| match reason.code()? {
+        "ConditionalCheckFailed" => Some(ErrorClass::ConditionalCheckFailed),
+        "TransactionConflict" => Some(ErrorClass::TransactionConflict),
+        "ThrottlingError" | "ProvisionedThroughputExceeded" => Some(ErrorClass::Throttled),
+        "ValidationError" | "ItemCollectionSizeLimitExceeded" => Some(ErrorClass::Validation),
+        _ => None,
+    })
+}
+
+macro_rules! request_error {
+    ($(#[$attr:meta])* $name:ident, $action:literal) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        #[non_exhaustive]
+        pub enum $name {
+            /// The request exceeded the table's provisioned throughput or an account request limit.
+            Throttled(ServiceError),
+            /// A condition on the request wasn't met.
+            ConditionalCheckFailed(ServiceError),
+            /// The table or index doesn't exist, or isn't active.
+            ResourceNotFound(ServiceError),
+            /// DynamoDB, or the SDK before sending, rejected the request as malformed.
+            Validation(ServiceError),
+            /// The request conflicted with a concurrent transaction.
+            TransactionConflict(ServiceError),
+            /// DynamoDB returned any other error.
+            Service(ServiceError),
+            /// The request timed out or never got a valid response.
+            Network(ServiceError),
+            /// A key or item in the request couldn't be serialized.
+            Serialize(SerializeError),
+            /// An item in the response couldn't be deserialized.
+            Deserialize(DeserializeError),
+            /// DynamoDB left this many keys or items of a batch unprocessed, even after retrying.
+            Unprocessed(usize),
+        }
+
+        impl $name {
+            /// Whether sending the same request again might succeed. Throttling, transaction
+            /// conflicts, network failures, server-side errors and unprocessed batches are
+            /// retryable.
+            pub fn is_retryable(&self) -> bool {
+                matches!(self, Self::Unprocessed(_))
+                    || self.service_error().is_some_and(ServiceError::is_retryable)
+            }
+
+            /// The ID DynamoDB assigned to the request, if it got that far.
+            pub fn request_id(&self) -> Option<&str> {
+                self.service_error().and_then(ServiceError::request_id)
+            }
+
+            /// The failed request, unless the error happened before sending or after receiving it.
+            pub fn service_error(&self) -> Option<&ServiceError> {
+                match self {
+                    Self::Throttled(e)
+                    | Self::ConditionalCheckFailed(e)
+                    | Self::ResourceNotFound(e)
+                    | Self::Validation(e)
+                    | Self::TransactionConflict(e)
+                    | Self::Service(e)
+                    | Self::Network(e) => Some(e),
+                    Self::Serialize(_) | Self::Deserialize(_) | Self::Unprocessed(_) => None,
+                }
+            }
+
+            fn from_service(e: ServiceError) -> Self {
+                match e.class {
+                    ErrorClass::Throttled => Self::Throttled(e),
+                    ErrorClass::ConditionalCheckFailed => Self::ConditionalCheckFailed(e),
+                    ErrorClass::ResourceNotFound => Self::ResourceNotFound(e),
+                    ErrorClass::Validation => Self::Validation(e),
+                    ErrorClass::TransactionConflict => Self::TransactionConflict(e),
+                    ErrorClass::Service => Self::Service(e),
+                    ErrorClass::Network => Self::Network(e),
+                }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::Throttled(e) => write!(f, "{} was throttled: {e}", $action),
+                    Self::ConditionalCheckFailed(e) => write!(f, "{} condition failed: {e}", $action),
+                    Self::ResourceNotFound(e) => write!(f, "{} table or index not found: {e}", $action),
+                    Self::Validation(e) => write!(f, "{} was invalid: {e}", $action),
+                    Self::TransactionConflict(e) => write!(f, "{} conflicted with a transaction: {e}", $action),
+                    Self::Service(e) => write!(f, "{} failed: {e}", $action),
+                    Self::Network(e) => write!(f, "{} couldn't reach DynamoDB: {e}", $action),
+                    Self::Serialize(e) => write!(f, "{} couldn't be serialized: {e}", $action),
+                    Self::Deserialize(e) => write!(f, "{} response couldn't be deserialized: {e}", $action),
+                    Self::Unprocessed(n) => write!(f, "{} left {n} keys or items unprocessed after retrying", $action),
+                }
+            }
+        }
+
+        impl std::error::Error for $name {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                match self.service_error() {
+                    Some(e) => Some(e),
+                    None => None,
+                }
+            }
+        }
+
+        impl From<SerializeError> for $name {
+            fn from(value: SerializeError) -> Self {
+                Self::Serialize(value)
+            }
+        }
+
+        impl From<DeserializeError> for $name {
+            fn from(value: DeserializeError) -> Self {
+                Self::Deserialize(value)
+            }
+        }
+    };
+}
+
+macro_rules! from_sdk_error {
+    ($name:ident: $($error:ty),* $(,)?) => {
+        $(
+            impl From<SdkError<$error, HttpResponse>> for $name {
+                fn from(value: SdkError<$error, HttpResponse>) -> Self {
+                    Self::from_service(ServiceError::new(value, None))
+                }
+            }
+        )*
+    };
+}
+
+request_error!(
+    /// Returned when reading items from DynamoDB fails.
+    ReadError,
+    "read"
+);
+
+request_error!(
+    /// Returned when writing items to DynamoDB fails.
+    WriteError,
+    "write"
+);
+
+from_sdk_error!(ReadError: GetItemError, QueryError, ScanError, BatchGetItemError);
+from_sdk_error!(WriteError: BatchWriteItemError);
+
+/// Conversions for single-item writes, whose condition failures return the current item.
+macro_rules! from_conditional_sdk_error {
+    ($name:ident: $($error:ident),* $(,)?) => {
+        $(
+            impl From<SdkError<$error, HttpResponse>> for $name {
+                fn from(value: SdkError<$error, HttpResponse>) -> Self {
+                    let item = match value.as_service_error() {
+                        Some($error::ConditionalCheckFailedException(e)) => e.item.clone(),
+                        _ => None,
+                    };
+                    Self::from_service(ServiceError::new(value, None).with_current_item(item))
+                }
+            }
+        )*
+    };
+}
+
+from_conditional_sdk_error!(WriteError: PutItemError, UpdateItemError, DeleteItemError);
+
+impl From<SdkError<TransactGetItemsError, HttpResponse>> for ReadError {
+    fn from(value: SdkError<TransactGetItemsError, HttpResponse>) -> Self {
+        let class = match value.as_service_error() {
+            Some(TransactGetItemsError::TransactionCanceledException(e)) => {
+                cancellation_class(e.cancellation_reasons())
+            }
+            _ => None,
+        };
+        Self::from_service(ServiceError::new(value, class))
+    }
+}
+
+impl WriteError {
+    /// The item as it was when the write's condition failed, read as `I`. See
+    /// [`ServiceError::current_item`].
+    pub fn current_item<I: item::Deserialize>(&self) -> Option<Result<I, DeserializeError>> {
+        match self {
+            Self::ConditionalCheckFailed(e) => e.current_item(),
+            _ => None,
+        }
+    }
+}
+
+impl From<SdkError<TransactWriteItemsError, HttpResponse>> for WriteError {
+    fn from(value: SdkError<TransactWriteItemsError, HttpResponse>) -> Self {
+        let class = match value.as_service_error() {
+            Some(TransactWriteItemsError::TransactionCanceledException(e)) => {
+                cancellation_class(e.cancellation_reasons())
+            }
+            _ => None,
+        };
+        Self::from_service(ServiceError::new(value, class))
+    }
+}
@@ -0,0 +1,709 @@
+//! Typed condition expressions.
+//!
+//! An [`Attr`] names an attribute and the [`value::Type`] stored in it, so conditions can only
+//! compare it to values of that type:
+//!
+//! ```
+//! use amo::expr::Attr;
+//! use amo::value::{N, S, SS};
+//!
+//! let version = Attr::<N>::new("version");
+//! let owner = Attr::<S>::new("owner");
+//! let labels = Attr::<SS>::new("labels");
+//! let condition = version
+//!     .lt(3_u64)
+//!     .and(owner.begins_with("team-").or(owner.is_in(["alice", "bob"])))
+//!     .and(labels.contains("prod"))
+//!     .and(labels.size().le(10_u32));
+//! ```
+//!
+//! Ordering comparisons are only available for the types DynamoDB can order, so they don't compile
+//! for an attribute of unknown type:
+//!
+//! ```compile_fail
+//! use amo::expr::Attr;
+//! use amo::value::Any;
+//! use aws_sdk_dynamodb::types::AttributeValue;
+//!
+//! let anything = Attr::<Any>::new("anything");
+//! let condition = anything.lt(AttributeValue::Bool(true));
+//! ```
+//!
+//! An `Attr` can also name a value nested in a map or list attribute, with [`field`](Attr::field)
+//! on a map and [`index`](Attr::index) on a list:
+//!
+//! ```
+//! use amo::expr::Attr;
+//! use amo::value::{L, M, S};
+//!
+//! let address = Attr::<M>::new("address");
+//! let city = address.field::<S>("city");
+//! let first_line = address.field::<L>("lines").index::<S>(0);
+//! let condition = city.eq("Paris").and(first_line.exists());
+//! ```
+//!
+//! The same [`Condition`] works as the condition of a put, update or delete and as the filter of
+//! a query or scan. Conditions are rendered when their request is sent. Every attribute name, and
+//! every map key in a nested path, goes through a placeholder, as does every value, so reserved
+//! words and names with special characters such as `.` need no escaping. [`Condition::into_expression`] renders one for use with the AWS SDK directly.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::Not;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::error::{PathSegment, SerializeError};
+use crate::value::{self, OrderedType, SetType, Type, B, L, M, N, S};
+
+/// The most values an `IN` comparison can list.
+const MAX_IN_VALUES: usize = 100;
+
+/// An attribute holding values of type `T`, or a value of type `T` nested inside one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Attr<T> {
+    path: Path,
+    _type: PhantomData<fn() -> T>,
+}
+
+/// The path to an attribute or nested value: an attribute name followed by map keys and list
+/// indexes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Path(Vec<PathSegment>);
+
+impl<T: Type> Attr<T> {
+    /// The top-level attribute `name`. The name is used as is, so a `.` or `[` in it is part of
+    /// the name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self::at(Path(vec![PathSegment::Field(name.into())]))
+    }
+
+    fn at(path: Path) -> Self {
+        Self {
+            path,
+            _type: PhantomData,
+        }
+    }
+
+    /// The name of the top-level attribute, which is this attribute unless it is nested.
+    pub fn name(&self) -> &str {
+        match &self.path.0[0] {
+            PathSegment::Field(name) => name,
+            PathSegment::Index(_) => unreachable!("paths start with an attribute name"),
+        }
+    }
+
+    /// The attribute name, map keys and list indexes leading to this value.
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path.0
+    }
+
+    pub(crate) fn to_path(&self) -> Path {
+        self.path.clone()
+    }
+
+    /// True if the item has this attribute.
+    pub fn exists(&self) -> Condition {
+        self.function("attribute_exists")
+    }
+
+    /// True if the item doesn't have this attribute.
+    pub fn not_exists(&self) -> Condition {
+        self.function("attribute_not_exists")
+    }
+
+    /// True if the attribute holds a value of type `V`. Mostly useful for [`Any`](value::Any)
+    /// attributes, whose type isn't known.
+    pub fn has_type<V: Type>(&self) -> Condition {
+        if V::NAME == value::Any::NAME {
+            return Condition::new(Err(self.path.wrap(SerializeError::invalid(
+                "attribute_type needs a concrete type",
+            ))));
+        }
+        Condition::new(Ok(Node::FunctionValue(
+            "attribute_type",
+            self.path.clone(),
+            AttributeValue::S(V::NAME.to_owned()),
+        )))
+    }
+
+    /// The size of the attribute: the length of a string or binary value, or the number of
+    /// elements in a set, list or map.
+    pub fn size(&self) -> Size {
+        Size {
+            path: self.path.clone(),
+        }
+    }
+
+    pub fn eq(&self, value: impl value::Serialize<Type = T>) -> Condition {
+        self.compare("=", value)
+    }
+
+    pub fn ne(&self, value: impl value::Serialize<Type = T>) -> Condition {
+        self.compare("<>", value)
+    }
+
+    /// True if the attribute equals one of `values`, of which there may be at most 100.
+    pub fn is_in<V: value::Serialize<Type = T>>(
+        &self,
+        values: impl IntoIterator<Item = V>,
+    ) -> Condition {
+        let values = values
+            .into_iter()
+            .map(|v| v.serialize_owned())
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|values| match values.len() {
+                0 => Err(SerializeError::invalid("IN needs at least one value")),
+                n if n > MAX_IN_VALUES => Err(SerializeError::invalid(format!(
+                    "IN takes at most {MAX_IN_VALUES} values, got {n}"
+                ))),
+                _ => Ok(values),
+            })
+            .map(|values| Node::In(Operand::Path(self.path.clone()), values))
+            .map_err(|e| self.path.wrap(e));
+        Condition::new(values)
+    }
+
+    fn function(&self, function: &'static str) -> Condition {
+        Condition::new(Ok(Node::Function(function, self.path.clone())))
+    }
+
+    fn function_value<V: Type>(
+        &self,
+        function: &'static str,
+        value: impl value::Serialize<Type = V>,
+    ) -> Condition {
+        let node = value
+            .serialize_owned()
+            .map(|value| Node::FunctionValue(function, self.path.clone(), value))
+            .map_err(|e| self.path.wrap(e));
+        Condition::new(node)
+    }
+
+    fn compare(&self, operator: &'static str, value: impl value::Serialize<Type = T>) -> Condition {
+        compare(Operand::Path(self.path.clone()), operator, value)
+    }
+}
+
+/// Ordering comparisons, for the scalar types DynamoDB can order.
+impl<T: OrderedType> Attr<T> {
+    pub fn lt(&self, value: impl value::Serialize<Type = T>) -> Condition {
+        self.compare("<", value)
+    }
+
+    pub fn le(&self, value: impl value::Serialize<Type = T>) -> Condition {
+        self.compare("<=", value)
+    }
+
+    pub fn gt(&self, value: impl value::Serialize<Type = T>) -> Condition {
+        self.compare(">", value)
+    }
+
+    pub fn ge(&self, value: impl value::Serialize<Type = T>) -> Condition {
+        self.compare(">=", value)
+    }
+
+    /// True if the attribute is at least `low` and at most `high`.
+    pub fn between(
+        &self,
+        low: impl value::Serialize<Type = T>,
+        high: impl value::Serialize<Type = T>,
+    ) -> Condition {
+        between(Operand::Path(self.path.clone()), low, high)
+    }
+}
+
+impl Attr<S> {
+    /// True if the string starts with `prefix`.
+    pub fn begins_with(&self, prefix: impl value::Serialize<Type = S>) -> Condition {
+        self.function_value("begins_with", prefix)
+    }
+
+    /// True if `substring` occurs in the string.
+    pub fn contains(&self, substring: impl value::Serialize<Type = S>) -> Condition {
+        self.function_value("contains", substring)
+    }
+}
+
+impl Attr<M> {
+    /// The value of type `V` stored under `key` in this map.
+    pub fn field<V: Type>(&self, key: impl Into<String>) -> Attr<V> {
+        Attr::at(self.path.join(PathSegment::Field(key.into())))
+    }
+}
+
+impl Attr<B> {
+    /// True if the binary value starts with the bytes of `prefix`.
+    pub fn begins_with(&self, prefix: impl value::Serialize<Type = B>) -> Condition {
+        self.function_value("begins_with", prefix)
+    }
+}
+
+impl<T: SetType> Attr<T> {
+    /// True if the set has `element`.
+    pub fn contains(&self, element: impl value::Serialize<Type = T::Element>) -> Condition {
+        self.function_value("contains", element)
+    }
+}
+
+impl Attr<L> {
+    /// The element of type `V` at `index` in this list.
+    pub fn index<V: Type>(&self, index: usize) -> Attr<V> {
+        Attr::at(self.path.join(PathSegment::Index(index)))
+    }
+
+    /// True if the list has an element equal to `element`.
+    pub fn contains<V: Type>(&self, element: impl value::Serialize<Type = V>) -> Condition {
+        self.function_value("contains", element)
+    }
+}
+
+/// The size of an attribute, from [`Attr::size`], for comparing to a number.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Size {
+    path: Path,
+}
+
+impl Size {
+    pub fn eq(&self, value: impl value::Serialize<Type = N>) -> Condition {
+        self.compare("=", value)
+    }
+
+    pub fn ne(&self, value: impl value::Serialize<Type = N>) -> Condition {
+        self.compare("<>", value)
+    }
+
+    pub fn lt(&self, value: impl value::Serialize<Type = N>) -> Condition {
+        self.compare("<", value)
+    }
+
+    pub fn le(&self, value: impl value::Serialize<Type = N>) -> Condition {
+        self.compare("<=", value)
+    }
+
+    pub fn gt(&self, value: impl value::Serialize<Type = N>) -> Condition {
+        self.compare(">", value)
+    }
+
+    pub fn ge(&self, value: impl value::Serialize<Type = N>) -> Condition {
+        self.compare(">=", value)
+    }
+
+    /// True if the size is at least `low` and at most `high`.
+    pub fn between(
+        &self,
+        low: impl value::Serialize<Type = N>,
+        high: impl value::Serialize<Type = N>,
+    ) -> Condition {
+        between(Operand::Size(self.path.clone()), low, high)
+    }
+
+    fn compare(&self, operator: &'static str, value: impl value::Serialize<Type = N>) -> Condition {
+        compare(Operand::Size(self.path.clone()), operator, value)
+    }
+}
+
+fn compare<T: Type>(
+    operand: Operand,
+    operator: &'static str,
+    value: impl value::Serialize<Type = T>,
+) -> Condition {
+    let node = value
+        .serialize_owned()
+        .map_err(|e| operand.path().wrap(e))
+        .map(|value| Node::Compare(operand, operator, value));
+    Condition::new(node)
+}
+
+fn between<T: Type>(
+    operand: Operand,
+    low: impl value::Serialize<Type = T>,
+    high: impl value::Serialize<Type = T>,
+) -> Condition {
+    let node = low
+        .serialize_owned()
+        .and_then(|low| high.serialize_owned().map(|high| (low, high)))
+        .map_err(|e| operand.path().wrap(e))
+        .map(|(low, high)| Node::Between(operand, low, high));
+    Condition::new(node)
+}
+
+/// A condition on an item, built from [`Attr`] methods. Combine conditions with
+/// [`and`](Self::and), [`or`](Self::or) and `!`.
+#[derive(Debug, Clone)]
+pub struct Condition {
+    /// The first value that failed to serialize is reported when the request is sent.
+    node: Result<Node, SerializeError>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Compare(Operand, &'static str, AttributeValue),
+    Between(Operand, AttributeValue, AttributeValue),
+    In(Operand, Vec<AttributeValue>),
+    Function(&'static str, Path),
+    FunctionValue(&'static str, Path, AttributeValue),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+}
+
+/// The left-hand side of a comparison.
+#[derive(Debug, Clone)]
+enum Operand {
+    Path(Path),
+    Size(Path),
+}
+
+/// A rendered condition, with the placeholders it uses. Returned by
+/// [`Condition::into_expression`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Expression {
+    pub expression: String,
+    /// The `ExpressionAttributeNames` map.
+    pub names: HashMap<String, String>,
+    /// The `ExpressionAttributeValues` map.
+    pub values: HashMap<String, AttributeValue>,
+}
+
+impl Condition {
+    fn new(node: Result<Node, SerializeError>) -> Self {
+        Self { node }
+    }
+
+    /// True if both conditions are.
+    pub fn and(self, other: Condition) -> Condition {
+        Self::combine(self, other, Node::And)
+    }
+
+    /// True if either condition is.
+    pub fn or(self, other: Condition) -> Condition {
+        Self::combine(self, other, Node::Or)
+    }
+
+    /// Renders the condition on its own, for requests amo doesn't build itself.
+    pub fn into_expression(self) -> Result<Expression, SerializeError> {
+        let mut placeholders = Placeholders::default();
+        let expression = self.render(&mut placeholders)?;
+        let (names, values) = placeholders.into_maps();
+        Ok(Expression {
+            expression,
+            names: names.unwrap_or_default(),
+            values: values.unwrap_or_default(),
+        })
+    }
+
+    fn combine(a: Condition, b: Condition, node: fn(Box<Node>, Box<Node>) -> Node) -> Condition {
+        Self::new(
+            a.node
+                .and_then(|a| b.node.map(|b| node(Box::new(a), Box::new(b)))),
+        )
+    }
+
+    /// Renders the condition, adding its names and values to `placeholders`.
+    pub(crate) fn render(self, placeholders: &mut Placeholders) -> Result<String, SerializeError> {
+        Ok(self.node?.render(placeholders))
+    }
+}
+
+impl Not for Condition {
+    type Output = Condition;
+
+    fn not(self) -> Condition {
+        Self::new(self.node.map(|node| Node::Not(Box::new(node))))
+    }
+}
+
+impl Node {
+    fn render(self, placeholders: &mut Placeholders) -> String {
+        match self {
+            Node::Compare(operand, operator, value) => {
+                let operand = operand.render(placeholders);
+                let value = placeholders.value(value);
+                format!("{operand} {operator} {value}")
+            }
+            Node::Between(operand, low, high) => {
+                let operand = operand.render(placeholders);
+                let low = placeholders.value(low);
+                let high = placeholders.value(high);
+                format!("{operand} BETWEEN {low} AND {high}")
+            }
+            Node::In(operand, values) => {
+                let operand = operand.render(placeholders);
+                let values: Vec<_> = values.into_iter().map(|v| placeholders.value(v)).collect();
+                format!("{operand} IN ({})", values.join(", "))
+            }
+            Node::Function(function, path) => format!("{function}({})", path.render(placeholders)),
+            Node::FunctionValue(function, path, value) => {
+                let path = path.render(placeholders);
+                format!("{function}({path}, {})", placeholders.value(value))
+            }
+            Node::And(a, b) => format!(
+                "({}) AND ({})",
+                a.render(placeholders),
+                b.render(placeholders)
+            ),
+            Node::Or(a, b) => format!(
+                "({}) OR ({})",
+                a.render(placeholders),
+                b.render(placeholders)
+            ),
+            Node::Not(a) => format!("NOT ({})", a.render(placeholders)),
+        }
+    }
+}
+
+impl Operand {
+    fn path(&self) -> &Path {
+        match self {
+            Operand::Path(path) | Operand::Size(path) => path,
+        }
+    }
+
+    fn render(self, placeholders: &mut Placeholders) -> String {
+        match self {
+            Operand::Path(path) => path.render(placeholders),
+            Operand::Size(path) => format!("size({})", path.render(placeholders)),
+        }
+    }
+}
+
+impl Path {
+    /// Renders the path with a placeholder for the attribute name and each map key, such as
+    /// `#n0.#n1[2]`.
+    pub(crate) fn render(&self, placeholders: &mut Placeholders) -> String {
+        let mut rendered = String::new();
+        for segment in &self.0 {
+            match segment {
+                PathSegment::Field(name) => {
+                    if !rendered.is_empty() {
+                        rendered.push('.');
+                    }
+                    rendered.push_str(&placeholders.name(name));
+                }
+                PathSegment::Index(index) => rendered.push_str(&format!("[{index}]")),
+            }
+        }
+        rendered
+    }
+
+    /// Records that `error` occurred at this path.
+    pub(crate) fn wrap(&self, error: SerializeError) -> SerializeError {
+        self.0
+            .iter()
+            .rev()
+            .fold(error, |error, segment| match segment {
+                PathSegment::Field(name) => error.at_field(name),
+                PathSegment::Index(index) => error.at_index(*index),
+            })
+    }
+
+    fn join(&self, segment: PathSegment) -> Path {
+        let mut path = self.0.clone();
+        path.push(segment);
+        Path(path)
+    }
+}
+
+/// The attribute name and value placeholders used by a request's expressions.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Placeholders {
+    /// Name placeholders in the order they were created, with the names they stand for.
+    names: Vec<(String, String)>,
+    values: HashMap<String, AttributeValue>,
+}
+
+impl Placeholders {
+    /// Returns the placeholder for `name`, creating one if it doesn't have one yet.
+    pub fn name(&mut self, name: &str) -> String {
+        if let Some((placeholder, _)) = self.names.iter().find(|(_, n)| n == name) {
+            return placeholder.clone();
+        }
+        let placeholder = format!("#n{}", self.names.len());
+        self.names.push((placeholder.clone(), name.to_owned()));
+        placeholder
+    }
+
+    /// Returns a new placeholder for `value`.
+    pub fn value(&mut self, value: AttributeValue) -> String {
+        let placeholder = format!(":v{}", self.values.len());
+        self.values.insert(placeholder.clone(), value);
+        placeholder
+    }
+
+    /// The `ExpressionAttributeNames` and `ExpressionAttributeValues` maps, each `None` if empty.
+    #[allow(clippy::type_complexity)]
+    pub fn into_maps(
+        self,
+    ) -> (
+        Option<HashMap<String, String>>,
+        Option<HashMap<String, AttributeValue>>,
+    ) {
+        let names = (!self.names.is_empty()).then(|| self.names.into_iter().collect());
+        let values = (!self.values.is_empty()).then_some(self.values);
+        (names, values)
+    }
+}
+
+/// Renders a `ProjectionExpression` selecting the top-level `attributes`.
+pub(crate) fn projection<'a>(
+    attributes: impl IntoIterator<Item = &'a str>,
+    placeholders: &mut Placeholders,
+) -> String {
+    attributes
+        .into_iter()
+        .map(|a| placeholders.name(a))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::value::{Any, SS};
+
+    use super::*;
+
+    fn n(n: &str) -> AttributeValue {
+        AttributeValue::N(n.to_owned())
+    }
+
+    fn s(s: &str) -> AttributeValue {
+        AttributeValue::S(s.to_owned())
+    }
+
+    fn names<const N: usize>(names: [(&str, &str); N]) -> HashMap<String, String> {
+        names
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v.to_owned()))
+            .collect()
+    }
+
+    fn values<const N: usize>(
+        values: [(&str, AttributeValue); N],
+    ) -> HashMap<String, AttributeValue> {
+        values.into_iter().map(|(k, v)| (k.to_owned(), v)).collect()
+    }
+
+    #[test]
+    fn placeholders() {
+        let version = Attr::<N>::new("version");
+        let owner = Attr::<S>::new("owner");
+        let expression = version
+            .lt(3_u64)
+            .and(owner.begins_with("team-").or(version.eq(7_u8)))
+            .into_expression()
+            .unwrap();
+        assert_eq!(
+            expression.expression,
+            "(#n0 < :v0) AND ((begins_with(#n1, :v1)) OR (#n0 = :v2))"
+        );
+        assert_eq!(
+            expression.names,
+            names([("#n0", "version"), ("#n1", "owner")])
+        );
+        assert_eq!(
+            expression.values,
+            values([(":v0", n("3")), (":v1", s("team-")), (":v2", n("7"))])
+        );
+    }
+
+    #[test]
+    fn operators() {
+        let render = |condition: Condition| condition.into_expression().unwrap().expression;
+        let size = Attr::<N>::new("size");
+        let name = Attr::<S>::new("name");
+        let labels = Attr::<SS>::new("labels");
+
+        assert_eq!(render(size.ne(1_u8)), "#n0 <> :v0");
+        assert_eq!(render(size.le(1_u8)), "#n0 <= :v0");
+        assert_eq!(render(size.gt(1_u8)), "#n0 > :v0");
+        assert_eq!(render(size.ge(1_u8)), "#n0 >= :v0");
+        assert_eq!(render(size.between(1_u8, 2_u8)), "#n0 BETWEEN :v0 AND :v1");
+        assert_eq!(render(name.is_in(["a", "b"])), "#n0 IN (:v0, :v1)");
+        assert_eq!(render(name.exists()), "attribute_exists(#n0)");
+        assert_eq!(
+            render(!name.not_exists()),
+            "NOT (attribute_not_exists(#n0))"
+        );
+        assert_eq!(render(name.contains("x")), "contains(#n0, :v0)");
+        assert_eq!(render(labels.contains("x")), "contains(#n0, :v0)");
+        assert_eq!(
+            render(labels.size().between(1_u8, 3_u8)),
+            "size(#n0) BETWEEN :v0 AND :v1"
+        );
+        assert_eq!(render(name.has_type::<N>()), "attribute_type(#n0, :v0)");
+    }
+
+    #[test]
+    fn nested_paths() {
+        let address = Attr::<M>::new("address");
+        let lines = address.field::<L>("lines");
+        let expression = address
+            .field::<S>("city")
+            .eq("Paris")
+            .and(lines.index::<S>(2).exists())
+            .and(lines.size().gt(1_u8))
+            .and(Attr::<M>::new("a.b").field::<N>("address").lt(1_u8))
+            .into_expression()
+            .unwrap();
+        assert_eq!(
+            expression.expression,
+            "(((#n0.#n1 = :v0) AND (attribute_exists(#n0.#n2[2]))) AND (size(#n0.#n2) > :v1)) \
+             AND (#n3.#n0 < :v2)"
+        );
+        assert_eq!(
+            expression.names,
+            names([
+                ("#n0", "address"),
+                ("#n1", "city"),
+                ("#n2", "lines"),
+                ("#n3", "a.b")
+            ])
+        );
+        assert_eq!(lines.index::<S>(2).name(), "address");
+        assert_eq!(
+            lines.index::<S>(2).path(),
+            [
+                PathSegment::Field("address".to_owned()),
+                PathSegment::Field("lines".to_owned()),
+                PathSegment::Index(2)
+            ]
+        );
+
+        let error = lines
+            .index::<N>(0)
+            .eq(f64::NAN)
+            .into_expression()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "at address.lines[0]: NaN and infinite numbers are not supported"
+        );
+    }
+
+    #[test]
+    fn invalid_conditions() {
+        let name = Attr::<S>::new("name");
+        let error = |condition: Condition| condition.into_expression().unwrap_err().to_string();
+
+        assert_eq!(
+            error(name.is_in(Vec::<String>::new())),
+            "at name: IN needs at least one value"
+        );
+        assert_eq!(
+            error(name.is_in((0..101).map(|i| i.to_string()))),
+            "at name: IN takes at most 100 values, got 101"
+        );
+        assert_eq!(
+            error(name.has_type::<Any>()),
+            "at name: attribute_type needs a concrete type"
+        );
+        // The first error wins, however the conditions are combined.
+        let size = Attr::<N>::new("size");
+        assert_eq!(
+            error(size.eq(f64::NAN).or(name.is_in(Vec::<String>::new()))),
+            "at size: NaN and infinite numbers are not supported"
+        );
+    }
+}
@@ -1,14 +1,42 @@
+//! Conversions between Rust types and whole DynamoDB items, usually derived with
+//! [`#[derive(Item)]`](crate::Item).
+//!
+//! Every attribute of an item needs a distinct name, so fields whose names collide after `rename`
+//! or `rename_all` are rejected:
+//!
+//! ```compile_fail
+//! #[derive(amo::Item)]
+//! #[amo(rename_all = "camelCase")]
+//! struct User {
+//!     user_id: String,
+//!     #[amo(rename = "userId")]
+//!     id: String,
+//! }
+//! ```
+//!
+//! The attributes of `flatten`ed fields aren't known until runtime, so collisions with those are
+//! reported by [`Serialize::serialize_to_map`] instead.
+
 use std::collections::HashMap;
 
 use aws_sdk_dynamodb::types::AttributeValue;
 
-use crate::error::{DeserializeError, SerializeError};
+use crate::error::{DeserializeError, SerializeError, SerializeErrorKind};
+use crate::value::Policy;
 
+/// The largest item DynamoDB stores, in bytes.
+pub const MAX_ITEM_SIZE: usize = 400 * 1024;
 
 pub trait Serialize {
+    /// How this type writes `None`s and empty sets to its attributes. Updates to items of this type
+    /// write values the same way; see [`UpdateItem::set`](crate::operation::UpdateItem::set).
+    const POLICY: Policy = Policy::DEFAULT;
+
     fn serialize(&self) -> impl Iterator<Item = Result<(String, AttributeValue), SerializeError>>;
 
-    fn serialize_owned(&self) -> impl Iterator<Item = Result<(String, AttributeValue), SerializeError>>
+    fn serialize_owned(
+        &self,
+    ) -> impl Iterator<Item = Result<(String, AttributeValue), SerializeError>>
     where
         Self: Sized,
     {
@@ -16,19 +44,106 @@ pub trait Serialize {
     }
 
     fn serialize_to_map(&self) -> Result<HashMap<String, AttributeValue>, SerializeError> {
-        self.serialize().collect()
+        check_size(collect(self.serialize())?)
     }
 
-    fn serialize_owned_to_map(self) -> Result<HashMap<String, AttributeValue>, SerializeError> where Self: Sized {
-        self.serialize_owned().collect()
+    fn serialize_owned_to_map(self) -> Result<HashMap<String, AttributeValue>, SerializeError>
+    where
+        Self: Sized,
+    {
+        check_size(collect(self.serialize_owned())?)
     }
 }
 
 pub trait Deserialize: Sized {
-    fn deserialize_owned(value: impl Iterator<Item = (String, AttributeValue)>) -> Result<Self, DeserializeError> {
+    fn deserialize_owned(
+        value: impl Iterator<Item = (String, AttributeValue)>,
+    ) -> Result<Self, DeserializeError> {
         // XX check duplicate keys?
         Deserialize::deserialize_owned_from_map(value.collect())
     }
 
-    fn deserialize_owned_from_map(value: HashMap<String, AttributeValue>) -> Result<Self, DeserializeError>;
+    fn deserialize_owned_from_map(
+        value: HashMap<String, AttributeValue>,
+    ) -> Result<Self, DeserializeError>;
+
+    /// The attributes this type reads, used to fetch only those attributes when it's the item type
+    /// of a projection. `None`, the default, means every attribute.
+    fn attributes() -> Option<Vec<&'static str>> {
+        None
+    }
+}
+
+/// Reads nothing, for operations that can return items when their items aren't wanted.
+impl Deserialize for () {
+    fn deserialize_owned_from_map(
+        _value: HashMap<String, AttributeValue>,
+    ) -> Result<Self, DeserializeError> {
+        Ok(())
+    }
+
+    fn attributes() -> Option<Vec<&'static str>> {
+        Some(Vec::new())
+    }
+}
+
+/// Collects serialized attributes into an item, rejecting attribute names that appear twice, such
+/// as a flattened field's attribute that collides with one of the outer item's.
+fn collect(
+    attributes: impl Iterator<Item = Result<(String, AttributeValue), SerializeError>>,
+) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+    let mut item = HashMap::new();
+    for attribute in attributes {
+        let (name, value) = attribute?;
+        if item.contains_key(&name) {
+            return Err(
+                SerializeError::invalid("item has more than one attribute with this name")
+                    .at_field(&name),
+            );
+        }
+        item.insert(name, value);
+    }
+    Ok(item)
+}
+
+fn check_size(
+    item: HashMap<String, AttributeValue>,
+) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+    let size = item
+        .iter()
+        .map(|(name, value)| name.len() + attribute_size(value))
+        .sum();
+    if size > MAX_ITEM_SIZE {
+        return Err(SerializeError::new(SerializeErrorKind::ItemTooLarge {
+            size,
+            limit: MAX_ITEM_SIZE,
+        }));
+    }
+    Ok(item)
+}
+
+/// The size DynamoDB counts for `value` against the item size limit.
+fn attribute_size(value: &AttributeValue) -> usize {
+    // Numbers take roughly one byte per two significant digits, plus one.
+    let number_size = |n: &str| {
+        let mantissa = n.split(['e', 'E']).next().unwrap_or(n);
+        mantissa.bytes().filter(u8::is_ascii_digit).count() / 2 + 1
+    };
+    match value {
+        AttributeValue::S(s) => s.len(),
+        AttributeValue::N(n) => number_size(n),
+        AttributeValue::B(b) => b.as_ref().len(),
+        AttributeValue::Bool(_) | AttributeValue::Null(_) => 1,
+        AttributeValue::L(l) => 3 + l.iter().map(|v| 1 + attribute_size(v)).sum::<usize>(),
+        AttributeValue::M(m) => {
+            3 + m
+                .iter()
+                .map(|(k, v)| 1 + k.len() + attribute_size(v))
+                .sum::<usize>()
+        }
+        AttributeValue::Ss(ss) => ss.iter().map(String::len).sum(),
+        AttributeValue::Ns(ns) => ns.iter().map(|n| number_size(n)).sum(),
+        AttributeValue::Bs(bs) => bs.iter().map(|b| b.as_ref().len()).sum(),
+        _ => 0,
+    }
 }
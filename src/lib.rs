@@ -1,6 +1,24 @@
+mod macros;
 
 pub mod error;
+pub mod expr;
 pub mod item;
-pub mod value;
+pub mod operation;
 pub mod table;
-pub mod operation;
\ No newline at end of file
+pub mod value;
+
+#[cfg(test)]
+mod test_support;
+
+pub use amo_derive::{Item, Table};
+
+// Lets the derives, which refer to `::amo`, be used by the unit tests.
+#[cfg(test)]
+extern crate self as amo;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use aws_sdk_dynamodb::{types::AttributeValue, Client};
+
+    pub use crate::macros::validate;
+}
@@ -0,0 +1,161 @@
+use std::fmt::Display;
+
+use crate::error::ValidationError;
+
+/// Implements [`value::Serialize`](crate::value::Serialize) and
+/// [`value::Deserialize`](crate::value::Deserialize) for a tuple-struct newtype by delegating to the
+/// wrapped value, along with conversions to and from the wrapped type.
+///
+/// The newtype has the same [`value::Type`](crate::value::Type) as the type it wraps, so a newtype
+/// over a `String` can be used anywhere an `S` key is expected.
+///
+/// ```
+/// # use amo::value_type;
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// pub struct AccountId(String);
+///
+/// value_type!(AccountId, String);
+/// ```
+///
+/// An optional `validate` hook runs whenever a value is deserialized. It receives a reference to
+/// the wrapped value and returns `Result<(), E>` for any `E: Display`. Validated newtypes get a
+/// `TryFrom` conversion from the wrapped type instead of `From`, which runs the same hook.
+///
+/// ```
+/// # use amo::value_type;
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// pub struct Arn(String);
+///
+/// value_type!(Arn, String, validate = |arn: &String| {
+///     if arn.starts_with("arn:") {
+///         Ok(())
+///     } else {
+///         Err("ARNs must start with \"arn:\"")
+///     }
+/// });
+///
+/// assert!(Arn::try_from("arn:aws:s3:::bucket".to_owned()).is_ok());
+/// assert!(Arn::try_from("bucket".to_owned()).is_err());
+/// ```
+#[macro_export]
+macro_rules! value_type {
+    ($name:ident, $inner:ty $(,)?) => {
+        $crate::value_type!(@impls $name, $inner);
+
+        impl ::core::convert::From<$inner> for $name {
+            fn from(inner: $inner) -> Self {
+                Self(inner)
+            }
+        }
+    };
+    ($name:ident, $inner:ty, validate = $validate:expr $(,)?) => {
+        $crate::value_type!(@impls $name, $inner, $validate);
+
+        impl ::core::convert::TryFrom<$inner> for $name {
+            type Error = $crate::error::ValidationError;
+
+            fn try_from(inner: $inner) -> ::core::result::Result<Self, Self::Error> {
+                $crate::__private::validate(stringify!($name), &inner, $validate)?;
+                Ok(Self(inner))
+            }
+        }
+    };
+    (@impls $name:ident, $inner:ty $(, $validate:expr)?) => {
+        impl ::core::convert::From<$name> for $inner {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl $crate::value::Serialize for $name {
+            type Type = <$inner as $crate::value::Serialize>::Type;
+
+            fn serialize_raw(
+                &self,
+            ) -> ::core::result::Result<<Self::Type as $crate::value::Type>::Raw, $crate::error::SerializeError> {
+                $crate::value::Serialize::serialize_raw(&self.0)
+            }
+
+            fn serialize_owned_raw(
+                self,
+            ) -> ::core::result::Result<<Self::Type as $crate::value::Type>::Raw, $crate::error::SerializeError> {
+                $crate::value::Serialize::serialize_owned_raw(self.0)
+            }
+
+            fn serialize(
+                &self,
+            ) -> ::core::result::Result<$crate::__private::AttributeValue, $crate::error::SerializeError> {
+                $crate::value::Serialize::serialize(&self.0)
+            }
+
+            fn serialize_owned(
+                self,
+            ) -> ::core::result::Result<$crate::__private::AttributeValue, $crate::error::SerializeError> {
+                $crate::value::Serialize::serialize_owned(self.0)
+            }
+
+            fn serialize_attribute(
+                &self,
+                policy: &$crate::value::Policy,
+            ) -> ::core::result::Result<
+                ::core::option::Option<$crate::__private::AttributeValue>,
+                $crate::error::SerializeError,
+            > {
+                $crate::value::Serialize::serialize_attribute(&self.0, policy)
+            }
+
+            fn serialize_owned_attribute(
+                self,
+                policy: &$crate::value::Policy,
+            ) -> ::core::result::Result<
+                ::core::option::Option<$crate::__private::AttributeValue>,
+                $crate::error::SerializeError,
+            > {
+                $crate::value::Serialize::serialize_owned_attribute(self.0, policy)
+            }
+        }
+
+        impl $crate::value::Deserialize for $name {
+            type Type = <$inner as $crate::value::Deserialize>::Type;
+
+            fn deserialize_owned_raw(
+                raw: <Self::Type as $crate::value::Type>::Raw,
+            ) -> ::core::result::Result<Self, $crate::error::DeserializeError> {
+                let inner = <$inner as $crate::value::Deserialize>::deserialize_owned_raw(raw)?;
+                $($crate::__private::validate(stringify!($name), &inner, $validate)?;)?
+                Ok(Self(inner))
+            }
+
+            fn deserialize_owned(
+                value: $crate::__private::AttributeValue,
+            ) -> ::core::result::Result<Self, $crate::error::DeserializeError> {
+                let inner = <$inner as $crate::value::Deserialize>::deserialize_owned(value)?;
+                $($crate::__private::validate(stringify!($name), &inner, $validate)?;)?
+                Ok(Self(inner))
+            }
+
+            fn deserialize_attribute(
+                value: ::core::option::Option<$crate::__private::AttributeValue>,
+                policy: &$crate::value::Policy,
+            ) -> ::core::result::Result<::core::option::Option<Self>, $crate::error::DeserializeError> {
+                let inner = <$inner as $crate::value::Deserialize>::deserialize_attribute(value, policy)?;
+                match inner {
+                    Some(inner) => {
+                        $($crate::__private::validate(stringify!($name), &inner, $validate)?;)?
+                        Ok(Some(Self(inner)))
+                    }
+                    None => Ok(None),
+                }
+            }
+        }
+    };
+}
+
+/// Runs the validation hook of a [`value_type!`] newtype.
+pub fn validate<T: ?Sized, E: Display>(
+    type_name: &'static str,
+    value: &T,
+    validate: impl FnOnce(&T) -> Result<(), E>,
+) -> Result<(), ValidationError> {
+    validate(value).map_err(|e| ValidationError::new(type_name, e))
+}
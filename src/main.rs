@@ -1,13 +1,8 @@
-use std::{
-    borrow::Cow, collections::HashMap, default, marker::PhantomData, ops::Deref, sync::Arc, time::Instant
-};
-
-use amo::{error::{DeserializeError, SerializeError}, item, operation::{GetItem, SKeyCondition, SKeyConditionBuilder}, table::{HashRangeTable, Table}, value::{self, Type, Value}, value_type};
-use aws_sdk_dynamodb::{
-    types::AttributeValue,
-    Client,
-};
+use std::time::SystemTime;
 
+use amo::{operation::Consistency, value_type};
+use aws_sdk_dynamodb::Client;
+use futures_util::StreamExt;
 
 #[tokio::main]
 async fn main() {
@@ -16,168 +11,96 @@ async fn main() {
     let shared_config = aws_config::load_from_env().await;
     let client = Client::new(&shared_config);
 
-    let table = TagTable {
-        name: Arc::new("tags".into()),
-        client,
-    };
+    let table = TagTable::new(client, "tags");
     let item = table
-        .get(Arn("abc".into()), "some-key")
-        // .consistency(Consistency::Strong)
+        .get(Arn("arn:aws:s3:::abc".into()), "some-key")
+        .unwrap()
+        .consistency(Consistency::Strong)
         .send()
         .await
         .unwrap()
         .item;
+    println!("{item:?}");
 
-    table
+    let mut tags = table
         .query()
-        .by_resource(Arn("abc".into()))
-        .all();
+        .by_resource(Arn("arn:aws:s3:::abc".into()))
+        .matching_key(|key| key.begins_with("some-"));
+    while let Some(tag) = tags.next().await {
+        println!("{tag:?}");
+    }
+
+    let mut tags = table.query().by_account("123456789012").descending();
+    while let Some(tag) = tags.next().await {
+        println!("{tag:?}");
+    }
 }
 
 // XX rename amors?
 
-#[derive(Debug, Clone, PartialEq, Eq)] //, amo::Table)]
+#[derive(Debug, Clone, PartialEq, Eq, amo::Item, amo::Table)]
 pub struct Tag {
-    // #[amo(primary, kind = hash)]
+    #[amo(primary, kind = hash)]
     resource: Arn,
 
-    // #[amo(primary, kind = range)]
-    // #[amo(secondary, index = "by-account", kind = range)]
+    #[amo(primary, kind = range)]
+    #[amo(secondary, index = "by-account", kind = range)]
     key: String,
 
     value: String,
 
     // view(arn)
-    // #[amo(secondary, index = "by-account", kind = hash)]
+    #[amo(secondary, index = "by-account", kind = hash)]
     account: String,
 
     // #[amo(version)]
     version: u64,
 
     // #[amo(created)]
-    created: Instant,
+    #[amo(with = epoch_millis)]
+    created: SystemTime,
 
     // #[amo(updated)]
-    updated: Instant,
-}
-
-impl item::Deserialize for Tag {
-    fn deserialize_owned_from_map(value: HashMap<String, AttributeValue>) -> Result<Self, DeserializeError> {
-        todo!()
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-// XX derive SerializeS
-pub struct Arn(String);
-
-impl value::Serialize for Arn {
-    type Type = value::S;
-
-    fn serialize_raw(&self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
-        Ok(self.0.clone())
-    }
-
-    fn serialize_owned_raw(self) -> Result<<Self::Type as Type>::Raw, SerializeError> where Self: Sized {
-        Ok(self.0)
-    }
-}
-
-impl value::Deserialize for Arn {
-    type Type = value::S;
-
-    fn deserialize_owned_raw(raw: <Self::Type as Type>::Raw) -> Result<Self, DeserializeError> {
-        Ok(Self(raw))
-    }
-}
-
-value_type!(Arn, S);
-
-// Derived
-#[derive(Debug, Clone)]
-pub struct TagTable {
-    name: Arc<String>,
-    client: Client,
+    #[amo(with = epoch_millis)]
+    updated: SystemTime,
 }
 
-impl Table for TagTable {
-    type Item = Tag;
+mod epoch_millis {
+    use std::time::{Duration, SystemTime};
 
-    fn name(&self) -> &str {
-        &self.name
-    }
-
-    fn client(&self) -> Client {
-        self.client.clone()
-    }
-}
-
-impl HashRangeTable for TagTable {
-    type HashKeyType = <Arn as Value>::Type;
-    const HASH_KEY_ATTRIBUTE: &'static str = "resource";
-
-    type RangeKeyType = <String as Value>::Type;
-    const RANGE_KEY_ATTRIBUTE: &'static str = "key";
-}
-
-impl TagTable {
-    pub fn get(&self, resource: impl Into<Arn>, key: impl Into<String>) -> GetItem<Self> {
-        self.get_raw(resource.into(), key.into())
-    }
-
-    pub fn query(&self) -> TagTableQuery<'_> {
-        TagTableQuery(self)
-    }
-}
-
-pub struct TagTableQuery<'a>(&'a TagTable);
-
-impl TagTableQuery<'_> {
-    pub fn by_resource(&self, resource: impl Into<Arn>) -> TagByResourceQuery<'_> {
-        self.by_resource_raw(resource.into())
+    use amo::{
+        error::{DeserializeError, SerializeError},
+        value::{Deserialize, Serialize},
+    };
+    use aws_sdk_dynamodb::types::AttributeValue;
+
+    pub fn serialize(time: &SystemTime) -> Result<AttributeValue, SerializeError> {
+        let millis = time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        millis.serialize_owned()
     }
 
-    pub fn by_resource_raw(&self, resource_key: impl value::Serialize<Type = <TagTable as HashRangeTable>::HashKeyType>) -> TagByResourceQuery<'_> {
-        TagByResourceQuery {
-            table: self.0,
-            resource_key: resource_key.serialize_owned_raw().unwrap(),
-        }
+    pub fn deserialize(value: AttributeValue) -> Result<SystemTime, DeserializeError> {
+        let millis = u64::deserialize_owned(value)?;
+        Ok(SystemTime::UNIX_EPOCH + Duration::from_millis(millis))
     }
-
-    // pub fn by_account(&self, account: impl Into<String>) -> TagByAccountQuery<'_> {
-    //     self.by_account_raw(account.into())
-    // }
-
-    // pub fn by_account_raw(&self, account_raw: impl SKey) -> TagByAccountQuery<'_> {
-    //     TagByAccountQuery {
-    //         table: self.0,
-    //         key: account_raw.serialize_owned(),
-    //     }
-    // }
 }
 
-pub struct TagByResourceQuery<'a> {
-    table: &'a TagTable,
-    resource_key: <<TagTable as HashRangeTable>::HashKeyType as Type>::Raw,
-}
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Arn(String);
 
-impl TagByResourceQuery<'_> {
-    pub fn all(self) -> PrimaryQuery<TagTable> {
-        todo!()
-    }
+value_type!(Arn, String, validate = |arn: &String| validate_arn(arn));
 
-    pub fn matching_key(
-        self,
-        key: impl FnOnce(SKeyConditionBuilder<Arn>) -> SKeyCondition,
-    ) -> PrimaryQuery<Self> {
-        todo!()
+fn validate_arn(arn: &str) -> Result<(), &'static str> {
+    if arn.starts_with("arn:") {
+        Ok(())
+    } else {
+        Err("ARNs must start with \"arn:\"")
     }
 }
 
-pub struct PrimaryQuery<T> {
-    table: T,
-}
-
 // impl TagTable {
 //     pub fn get<'a>(&self, resource: impl Into<Arn>, key: impl Into<String>) -> GetItem<Self> {
 //         self.get_raw(resource.into(), key.into())
@@ -188,7 +111,6 @@ pub struct PrimaryQuery<T> {
 //     }
 // }
 
-
 // impl TagTableQuery<'_> {
 //     pub fn by_resource(&self, resource: impl Into<Arn>) -> TagByResourceQuery<'_> {
 //         self.by_resource_raw(resource.into())
@@ -1,21 +1,33 @@
-
+mod backoff;
+pub mod batch_get;
+pub mod batch_write;
+pub mod delete_item;
 pub mod get_item;
-use std::marker::PhantomData;
+pub mod key_condition;
+pub mod pages;
+pub mod put_item;
+pub mod query;
+pub mod scan;
+pub mod update_item;
 
+pub use batch_get::BatchGet;
+pub use batch_write::{BatchWrite, FailedWrite};
+pub use delete_item::{DeleteItem, DeleteItemOutput};
 pub use get_item::{GetItem, GetItemOutput};
+pub use key_condition::{KeyCondition, KeyConditionBuilder};
+pub use pages::{Page, Pages};
+pub use put_item::{PutItem, PutItemOutput};
+pub use query::Query;
+pub use scan::Scan;
+pub use update_item::{UpdateItem, UpdateItemOutput};
 
-use crate::value::{Value, S};
-
-pub struct SKeyConditionBuilder<T> {
-    _value: PhantomData<T>
-}
-
-pub struct SKeyCondition {
-
+/// The consistency of a read. See the
+/// [DynamoDB documentation](https://docs.aws.amazon.com/amazondynamodb/latest/developerguide/HowItWorks.ReadConsistency.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Consistency {
+    /// The read may not reflect recently completed writes.
+    #[default]
+    Eventual,
+    /// The read reflects every write that succeeded before it. Costs twice as much capacity.
+    Strong,
 }
-
-impl<T: Value<Type = S>> SKeyConditionBuilder<T> {
-    pub fn equals(self, value: T) -> SKeyCondition {
-        todo!()
-    }
-}
\ No newline at end of file
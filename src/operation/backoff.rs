@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+/// The delay before the first retry, before jitter.
+const BASE_DELAY: Duration = Duration::from_millis(50);
+
+/// The longest delay between retries, before jitter.
+const MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Exponential backoff with full jitter, for retrying the unprocessed part of a batch.
+#[derive(Debug, Clone)]
+pub(crate) struct Backoff {
+    attempt: u32,
+    max_retries: u32,
+}
+
+impl Backoff {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            attempt: 0,
+            max_retries,
+        }
+    }
+
+    /// Waits before the next retry, or returns `false` without waiting if no retries are left.
+    pub async fn wait(&mut self) -> bool {
+        if self.attempt >= self.max_retries {
+            return false;
+        }
+        let ceiling = BASE_DELAY
+            .saturating_mul(1 << self.attempt.min(16))
+            .min(MAX_DELAY);
+        self.attempt += 1;
+        tokio::time::sleep(ceiling.mul_f64(fastrand::f64())).await;
+        true
+    }
+}
@@ -0,0 +1,520 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, KeysAndAttributes},
+    Client,
+};
+use futures_core::Stream;
+use futures_util::future::{BoxFuture, FutureExt};
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+
+use crate::{
+    error::{DeserializeError, ReadError, SerializeError},
+    expr, item,
+    table::Table,
+};
+
+use super::backoff::Backoff;
+use super::Consistency;
+
+/// The most keys one `BatchGetItem` request can read.
+const MAX_KEYS_PER_REQUEST: usize = 100;
+
+type Key = HashMap<String, AttributeValue>;
+
+/// Reads many items by key. Returned by the `batch_get` methods of tables.
+///
+/// Repeated keys are read once. The keys are split into `BatchGetItem` requests of up to 100,
+/// several of which are sent at once. Keys DynamoDB leaves unprocessed, and requests that fail with
+/// a [retryable](ReadError::is_retryable) error such as throttling, are retried with exponential
+/// backoff and jitter.
+///
+/// A `BatchGet` is a [`Stream`] of each item found, read as `I`, with its key, in no particular
+/// order. Keys without an item are left out. Collect it with [`into_map`](Self::into_map), or
+/// poll it to handle items as they arrive. Keys that are still unprocessed once the retries run
+/// out, or whose request failed, are reported with an error and listed by
+/// [`unprocessed`](Self::unprocessed) while the stream is polled.
+pub struct BatchGet<T: Table, K, I = <T as Table>::Item> {
+    client: Client,
+    table_name: String,
+    keys: Vec<K>,
+    key_attributes: Vec<&'static str>,
+    serialize_key: fn(&K) -> Result<Key, SerializeError>,
+    deserialize_key: fn(&Key) -> Result<K, DeserializeError>,
+    projection: Option<Vec<&'static str>>,
+    consistency: Consistency,
+    concurrency: usize,
+    max_retries: u32,
+    unprocessed: Vec<K>,
+    running: Option<Running>,
+    _table: PhantomData<fn() -> (T, I)>,
+}
+
+struct Running {
+    requests: BoxStream<'static, (Response, Option<ReadError>)>,
+    buffer: VecDeque<HashMap<String, AttributeValue>>,
+    done: bool,
+}
+
+/// What one chunk of keys read, or one attempt at it.
+struct Response {
+    items: Vec<HashMap<String, AttributeValue>>,
+    unprocessed: Vec<Key>,
+}
+
+impl<T: Table, K: Hash + Eq + Clone> BatchGet<T, K> {
+    pub(crate) fn new(
+        table: &T,
+        keys: impl IntoIterator<Item = K>,
+        key_attributes: Vec<&'static str>,
+        serialize_key: fn(&K) -> Result<Key, SerializeError>,
+        deserialize_key: fn(&Key) -> Result<K, DeserializeError>,
+    ) -> Self {
+        let mut seen = HashSet::new();
+        let keys = keys
+            .into_iter()
+            .filter(|key| seen.insert(key.clone()))
+            .collect();
+        Self {
+            client: table.client(),
+            table_name: table.name().to_owned(),
+            keys,
+            key_attributes,
+            serialize_key,
+            deserialize_key,
+            projection: None,
+            consistency: Consistency::Eventual,
+            concurrency: 4,
+            max_retries: 8,
+            unprocessed: Vec::new(),
+            running: None,
+            _table: PhantomData,
+        }
+    }
+}
+
+impl<T: Table, K, I: item::Deserialize> BatchGet<T, K, I> {
+    /// Sets how many requests are in flight at once. Defaults to 4.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets how many times unprocessed keys are retried before giving up. Defaults to 8.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets whether the reads are strongly consistent. Reads are eventually consistent by default.
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    /// Reads items as `P`, fetching only the attributes `P` reads and the key attributes.
+    pub fn project<P: item::Deserialize>(self) -> BatchGet<T, K, P> {
+        BatchGet {
+            client: self.client,
+            table_name: self.table_name,
+            keys: self.keys,
+            key_attributes: self.key_attributes,
+            serialize_key: self.serialize_key,
+            deserialize_key: self.deserialize_key,
+            projection: P::attributes(),
+            consistency: self.consistency,
+            concurrency: self.concurrency,
+            max_retries: self.max_retries,
+            unprocessed: Vec::new(),
+            running: None,
+            _table: PhantomData,
+        }
+    }
+
+    /// The keys that weren't read, because DynamoDB left them unprocessed after every retry or
+    /// their request failed. Complete once the stream has ended.
+    pub fn unprocessed(&self) -> &[K] {
+        &self.unprocessed
+    }
+
+    /// Reads every item, keyed by its key, along with every error. An error for one request or
+    /// item doesn't lose the items already read, or stop the rest from being read. Poll the
+    /// stream instead to get the [`unprocessed`](Self::unprocessed) keys back.
+    pub async fn into_map(mut self) -> (HashMap<K, I>, Vec<ReadError>)
+    where
+        K: Hash + Eq,
+    {
+        let mut items = HashMap::with_capacity(self.keys.len());
+        let mut errors = Vec::new();
+        while let Some(item) = self.next().await {
+            match item {
+                Ok((key, item)) => {
+                    items.insert(key, item);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+        (items, errors)
+    }
+
+    fn start(&mut self) -> Result<Running, SerializeError> {
+        let keys = self
+            .keys
+            .iter()
+            .map(self.serialize_key)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut placeholders = expr::Placeholders::default();
+        let projection = self.projection.as_ref().map(|attributes| {
+            let keys = self.key_attributes.iter();
+            let others = attributes
+                .iter()
+                .filter(|a| !self.key_attributes.contains(a));
+            expr::projection(keys.chain(others).copied(), &mut placeholders)
+        });
+        let (names, _) = placeholders.into_maps();
+
+        let request = Request {
+            client: self.client.clone(),
+            table_name: self.table_name.clone(),
+            projection,
+            names,
+            consistent_read: self.consistency == Consistency::Strong,
+            max_retries: self.max_retries,
+        };
+        let chunks: Vec<Vec<Key>> = keys
+            .chunks(MAX_KEYS_PER_REQUEST)
+            .map(<[Key]>::to_vec)
+            .collect();
+        let requests = stream::iter(chunks)
+            .map(move |keys| request.clone().send(keys))
+            .buffer_unordered(self.concurrency)
+            .boxed();
+
+        Ok(Running {
+            requests,
+            buffer: VecDeque::new(),
+            done: false,
+        })
+    }
+
+    /// Records keys that weren't read, returning how many there were.
+    fn record_unprocessed(&mut self, keys: Vec<Key>) -> usize {
+        let count = keys.len();
+        self.unprocessed.extend(
+            keys.iter()
+                .filter_map(|key| (self.deserialize_key)(key).ok()),
+        );
+        count
+    }
+}
+
+// Keys and items are never pinned, only buffered.
+impl<T: Table, K, I> Unpin for BatchGet<T, K, I> {}
+
+impl<T: Table, K, I: item::Deserialize> Stream for BatchGet<T, K, I> {
+    type Item = Result<(K, I), ReadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.running.is_none() {
+            match this.start() {
+                Ok(running) => this.running = Some(running),
+                Err(e) => {
+                    this.running = Some(Running {
+                        requests: stream::empty().boxed(),
+                        buffer: VecDeque::new(),
+                        done: true,
+                    });
+                    return Poll::Ready(Some(Err(e.into())));
+                }
+            }
+        }
+
+        loop {
+            let running = this.running.as_mut().expect("the batch was just started");
+            if let Some(item) = running.buffer.pop_front() {
+                let key = (this.deserialize_key)(&item);
+                let item = key.and_then(|key| Ok((key, I::deserialize_owned_from_map(item)?)));
+                return Poll::Ready(Some(item.map_err(ReadError::from)));
+            }
+            if running.done {
+                return Poll::Ready(None);
+            }
+
+            match running.requests.poll_next_unpin(cx) {
+                Poll::Ready(Some((response, error))) => {
+                    running.buffer.extend(response.items);
+                    if !response.unprocessed.is_empty() {
+                        let count = this.record_unprocessed(response.unprocessed);
+                        let error = error.unwrap_or(ReadError::Unprocessed(count));
+                        return Poll::Ready(Some(Err(error)));
+                    }
+                }
+                Poll::Ready(None) => running.done = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Everything needed to send one chunk of keys.
+#[derive(Clone)]
+struct Request {
+    client: Client,
+    table_name: String,
+    projection: Option<String>,
+    names: Option<HashMap<String, String>>,
+    consistent_read: bool,
+    max_retries: u32,
+}
+
+impl Request {
+    /// Reads `keys`, retrying any left unprocessed. See [`retry`].
+    async fn send(self, keys: Vec<Key>) -> (Response, Option<ReadError>) {
+        let backoff = Backoff::new(self.max_retries);
+        retry(backoff, keys, |keys| self.send_once(keys).boxed()).await
+    }
+
+    /// Sends one `BatchGetItem` request, returning the items read and the keys left unprocessed.
+    async fn send_once(&self, keys: Vec<Key>) -> Result<Response, ReadError> {
+        let request = KeysAndAttributes::builder()
+            .set_keys(Some(keys))
+            .set_projection_expression(self.projection.clone())
+            .set_expression_attribute_names(self.names.clone())
+            .consistent_read(self.consistent_read)
+            .build()
+            .expect("keys are set");
+        let output = self
+            .client
+            .batch_get_item()
+            .request_items(&self.table_name, request)
+            .send()
+            .await?;
+        Ok(Response {
+            items: output
+                .responses
+                .and_then(|mut responses| responses.remove(&self.table_name))
+                .unwrap_or_default(),
+            unprocessed: output
+                .unprocessed_keys
+                .and_then(|mut unprocessed| unprocessed.remove(&self.table_name))
+                .map(|unprocessed| unprocessed.keys)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Reads `keys` with `send`, retrying the keys left unprocessed and requests that fail with a
+/// retryable error while `backoff` allows. Returns every item read and the keys that weren't, along with the error that
+/// stopped the reads, if any.
+async fn retry<'a>(
+    mut backoff: Backoff,
+    mut keys: Vec<Key>,
+    mut send: impl FnMut(Vec<Key>) -> BoxFuture<'a, Result<Response, ReadError>>,
+) -> (Response, Option<ReadError>) {
+    let mut items = Vec::new();
+    let error = loop {
+        match send(keys.clone()).await {
+            Ok(response) => {
+                items.extend(response.items);
+                keys = response.unprocessed;
+                if keys.is_empty() || !backoff.wait().await {
+                    break None;
+                }
+            }
+            Err(e) if e.is_retryable() => {
+                if !backoff.wait().await {
+                    break Some(e);
+                }
+            }
+            Err(e) => break Some(e),
+        }
+    };
+    let response = Response {
+        items,
+        unprocessed: keys,
+    };
+    (response, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use aws_sdk_dynamodb::{
+        config::http::HttpResponse,
+        error::{ErrorMetadata, SdkError},
+        operation::batch_get_item::BatchGetItemError,
+    };
+
+    use crate::table::HashTable;
+    use crate::test_support::{client, Thing, ThingTable};
+
+    use super::*;
+
+    fn key(id: &str) -> Key {
+        HashMap::from([("id".to_owned(), AttributeValue::S(id.to_owned()))])
+    }
+
+    fn service_error(code: &str) -> ReadError {
+        service_error_with_status(code, 400)
+    }
+
+    fn service_error_with_status(code: &str, status: u16) -> ReadError {
+        let error = BatchGetItemError::generic(ErrorMetadata::builder().code(code).build());
+        let response = HttpResponse::new(status.try_into().unwrap(), "".into());
+        SdkError::service_error(error, response).into()
+    }
+
+    /// Replays `attempts` in order, recording the keys each was sent.
+    async fn replay(
+        max_retries: u32,
+        attempts: Vec<Result<Response, ReadError>>,
+    ) -> ((Response, Option<ReadError>), Vec<Vec<Key>>) {
+        let sent = Mutex::new(Vec::new());
+        let mut attempts = attempts.into_iter();
+        let result = retry(
+            Backoff::new(max_retries),
+            vec![key("a"), key("b")],
+            |keys| {
+                sent.lock().unwrap().push(keys);
+                let attempt = attempts.next().expect("no more attempts");
+                async move { attempt }.boxed()
+            },
+        )
+        .await;
+        (result, sent.into_inner().unwrap())
+    }
+
+    #[tokio::test]
+    async fn retries_unprocessed_keys() {
+        let ((response, error), sent) = replay(
+            8,
+            vec![
+                Ok(Response {
+                    items: vec![key("a")],
+                    unprocessed: vec![key("b")],
+                }),
+                Ok(Response {
+                    items: vec![key("b")],
+                    unprocessed: Vec::new(),
+                }),
+            ],
+        )
+        .await;
+        assert!(error.is_none());
+        assert_eq!(response.items, [key("a"), key("b")]);
+        assert!(response.unprocessed.is_empty());
+        assert_eq!(sent, [vec![key("a"), key("b")], vec![key("b")]]);
+    }
+
+    #[tokio::test]
+    async fn retries_throttling() {
+        let ((response, error), sent) = replay(
+            8,
+            vec![
+                Err(service_error("ThrottlingException")),
+                Err(service_error("ProvisionedThroughputExceededException")),
+                Ok(Response {
+                    items: vec![key("a"), key("b")],
+                    unprocessed: Vec::new(),
+                }),
+            ],
+        )
+        .await;
+        assert!(error.is_none());
+        assert_eq!(response.items.len(), 2);
+        assert_eq!(sent.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn retries_network_and_server_errors() {
+        let ((response, error), sent) = replay(
+            8,
+            vec![
+                Err(SdkError::<BatchGetItemError, HttpResponse>::timeout_error("slow").into()),
+                Err(service_error_with_status("InternalServerError", 500)),
+                Err(service_error_with_status("SomethingElse", 503)),
+                Ok(Response {
+                    items: vec![key("a"), key("b")],
+                    unprocessed: Vec::new(),
+                }),
+            ],
+        )
+        .await;
+        assert!(error.is_none());
+        assert_eq!(response.items.len(), 2);
+        assert_eq!(sent.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn keeps_items_read_before_an_error() {
+        let ((response, error), sent) = replay(
+            1,
+            vec![
+                Ok(Response {
+                    items: vec![key("a")],
+                    unprocessed: vec![key("b")],
+                }),
+                Err(service_error("ThrottlingException")),
+            ],
+        )
+        .await;
+        assert!(matches!(error, Some(ReadError::Throttled(_))));
+        assert_eq!(response.items, [key("a")]);
+        assert_eq!(response.unprocessed, [key("b")]);
+        assert_eq!(sent.len(), 2);
+
+        // Other errors aren't retried.
+        let ((response, error), sent) =
+            replay(8, vec![Err(service_error("ValidationException"))]).await;
+        assert!(matches!(error, Some(ReadError::Validation(_))));
+        assert!(response.items.is_empty());
+        assert_eq!(response.unprocessed.len(), 2);
+        assert_eq!(sent.len(), 1);
+    }
+
+    fn thing(id: &str) -> Thing {
+        Thing { id: id.to_owned() }
+    }
+
+    #[tokio::test]
+    async fn into_map_keeps_partial_results() {
+        let mut batch =
+            ThingTable::new(client(), "things").batch_get(["a", "b", "c"].map(str::to_owned));
+        let responses = [
+            (
+                Response {
+                    items: vec![key("a")],
+                    unprocessed: Vec::new(),
+                },
+                None,
+            ),
+            (
+                Response {
+                    items: vec![key("b")],
+                    unprocessed: vec![key("c")],
+                },
+                Some(service_error("ThrottlingException")),
+            ),
+        ];
+        batch.running = Some(Running {
+            requests: stream::iter(responses).boxed(),
+            buffer: VecDeque::new(),
+            done: false,
+        });
+
+        let (items, errors) = batch.into_map().await;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items["a"], thing("a"));
+        assert_eq!(items["b"], thing("b"));
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], ReadError::Throttled(_)));
+    }
+}
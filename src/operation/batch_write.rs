@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, DeleteRequest, PutRequest, WriteRequest},
+    Client,
+};
+
+use crate::{
+    error::WriteError,
+    item,
+    table::{Key, PrimaryKey, Table},
+};
+
+use super::backoff::Backoff;
+
+/// The most writes one `BatchWriteItem` request can make.
+const MAX_WRITES_PER_REQUEST: usize = 25;
+
+/// Puts and deletes many items, in any number of tables, in batches.
+///
+/// Writes are queued for each table and sent in `BatchWriteItem` requests of 25, using the
+/// client of the table they were queued for. DynamoDB rejects a request that writes the same item
+/// twice, so a write to a key already queued sends the table's queued writes first. Writes
+/// DynamoDB leaves unprocessed are retried with exponential backoff and jitter.
+///
+/// Writes that still fail, because they were unprocessed after every retry or their request
+/// failed, are listed by [`failures`](Self::failures). Finish with [`flush`](Self::flush), which
+/// sends the rest and returns every failed write. Writes still queued when a writer is dropped
+/// without it, for example by returning early with `?`, are never sent.
+#[derive(Debug)]
+#[must_use = "queued writes are only sent once the writer is flushed"]
+pub struct BatchWrite {
+    /// Each table's client and queued writes, in the order the tables were first written to.
+    pending: Vec<(String, Client, Vec<Write>)>,
+    failures: Vec<FailedWrite>,
+    max_retries: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Write {
+    key: HashMap<String, AttributeValue>,
+    request: WriteRequest,
+}
+
+/// A put or delete that didn't happen.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FailedWrite {
+    pub table_name: String,
+    /// The key of the item that wasn't written or deleted.
+    pub key: HashMap<String, AttributeValue>,
+    /// The put or delete request itself.
+    pub request: WriteRequest,
+    /// Why it failed. Writes from the same request share the error.
+    pub error: Arc<WriteError>,
+}
+
+impl Default for BatchWrite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchWrite {
+    /// A writer with nothing queued. Each table's writes are sent with that table's client.
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            failures: Vec::new(),
+            max_retries: 8,
+        }
+    }
+
+    /// Sets how many times unprocessed writes are retried before giving up. Defaults to 8.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Queues writing `item` to `table`, replacing any item with the same key. Sends a batch if
+    /// one fills up, returning its error if it fails.
+    pub async fn put<T, M>(&mut self, table: &T, item: &T::Item) -> Result<(), WriteError>
+    where
+        T: PrimaryKey<M>,
+        T::Item: item::Serialize,
+    {
+        let item = item::Serialize::serialize_to_map(item)?;
+        let key = T::key_of(&item)?;
+        let request = WriteRequest::builder()
+            .put_request(
+                PutRequest::builder()
+                    .set_item(Some(item))
+                    .build()
+                    .expect("item is set"),
+            )
+            .build();
+        self.push(table, key, request).await
+    }
+
+    /// Queues deleting the item with `key` from `table`: a hash key, or a `(hash, range)` pair.
+    /// Sends a batch if one fills up, returning its error if it fails.
+    pub async fn delete<T, M>(&mut self, table: &T, key: impl Key<T, M>) -> Result<(), WriteError>
+    where
+        T: Table,
+    {
+        let key = key.serialize_key()?;
+        let request = WriteRequest::builder()
+            .delete_request(
+                DeleteRequest::builder()
+                    .set_key(Some(key.clone()))
+                    .build()
+                    .expect("key is set"),
+            )
+            .build();
+        self.push(table, key, request).await
+    }
+
+    /// Sends every queued write, retrying unprocessed ones, and returns every write that failed,
+    /// including those already reported by [`put`](Self::put) and [`delete`](Self::delete). Each
+    /// failure carries its request's error.
+    pub async fn flush(mut self) -> Vec<FailedWrite> {
+        for (table_name, client, writes) in mem::take(&mut self.pending) {
+            // The error is recorded with the failed writes.
+            let _ = self.send(&table_name, &client, writes).await;
+        }
+        self.failures
+    }
+
+    /// The writes that failed so far.
+    pub fn failures(&self) -> &[FailedWrite] {
+        &self.failures
+    }
+
+    /// Removes and returns the writes that failed so far.
+    pub fn take_failures(&mut self) -> Vec<FailedWrite> {
+        mem::take(&mut self.failures)
+    }
+
+    async fn push<T: Table>(
+        &mut self,
+        table: &T,
+        key: HashMap<String, AttributeValue>,
+        request: WriteRequest,
+    ) -> Result<(), WriteError> {
+        let table_name = table.name();
+        let index = match self
+            .pending
+            .iter()
+            .position(|(name, ..)| name == table_name)
+        {
+            Some(index) => index,
+            None => {
+                self.pending
+                    .push((table_name.to_owned(), table.client(), Vec::new()));
+                self.pending.len() - 1
+            }
+        };
+
+        // Sending the queued writes first keeps the writes in order, so the later one wins.
+        let duplicate = self.pending[index].2.iter().any(|write| write.key == key);
+        let mut result = if duplicate {
+            self.flush_table(index).await
+        } else {
+            Ok(())
+        };
+
+        let writes = &mut self.pending[index].2;
+        writes.push(Write { key, request });
+        if writes.len() >= MAX_WRITES_PER_REQUEST {
+            let flushed = self.flush_table(index).await;
+            result = result.and(flushed);
+        }
+        result
+    }
+
+    /// Sends the writes queued for the table at `index` of `pending`.
+    async fn flush_table(&mut self, index: usize) -> Result<(), WriteError> {
+        let (table_name, client, writes) = &mut self.pending[index];
+        let writes = mem::take(writes);
+        let (table_name, client) = (table_name.clone(), client.clone());
+        self.send(&table_name, &client, writes).await
+    }
+
+    /// Sends `pending` to `table_name`, retrying unprocessed writes, and records the writes that
+    /// fail.
+    async fn send(
+        &mut self,
+        table_name: &str,
+        client: &Client,
+        mut pending: Vec<Write>,
+    ) -> Result<(), WriteError> {
+        let mut backoff = Backoff::new(self.max_retries);
+        while !pending.is_empty() {
+            let requests = pending.iter().map(|write| write.request.clone()).collect();
+            let output = match client
+                .batch_write_item()
+                .request_items(table_name, requests)
+                .send()
+                .await
+            {
+                Ok(output) => output,
+                Err(e) => {
+                    let error = WriteError::from(e);
+                    self.fail(table_name, pending, error.clone());
+                    return Err(error);
+                }
+            };
+
+            let unprocessed = output
+                .unprocessed_items
+                .and_then(|mut unprocessed| unprocessed.remove(table_name))
+                .unwrap_or_default();
+            pending.retain(|write| unprocessed.contains(&write.request));
+            if !pending.is_empty() && !backoff.wait().await {
+                let error = WriteError::Unprocessed(pending.len());
+                self.fail(table_name, pending, error.clone());
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    /// Records `writes` to `table_name` as failed with `error`.
+    fn fail(&mut self, table_name: &str, writes: Vec<Write>, error: WriteError) {
+        let error = Arc::new(error);
+        self.failures
+            .extend(writes.into_iter().map(|write| FailedWrite {
+                table_name: table_name.to_owned(),
+                key: write.key,
+                request: write.request,
+                error: error.clone(),
+            }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodb::config::retry::RetryConfig;
+    use aws_sdk_dynamodb::config::{BehaviorVersion, Credentials, Region};
+    use aws_sdk_dynamodb::Config;
+
+    use crate::test_support::ThingTable;
+
+    use super::*;
+
+    /// A client whose requests fail without leaving the machine: with nothing listening on its
+    /// endpoint, or with no region to find an endpoint for.
+    fn client(region: bool) -> Client {
+        let mut config = Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .credentials_provider(Credentials::new("id", "secret", None, None, "test"))
+            .retry_config(RetryConfig::disabled());
+        if region {
+            config = config
+                .region(Region::new("us-east-1"))
+                .endpoint_url("http://127.0.0.1:1");
+        }
+        Client::from_conf(config.build())
+    }
+
+    #[tokio::test]
+    async fn failed_requests_are_classified_per_table() {
+        let unreachable = ThingTable::new(client(true), "unreachable");
+        let unconfigured = ThingTable::new(client(false), "unconfigured");
+
+        let mut batch = BatchWrite::new();
+        batch.delete(&unreachable, "a").await.unwrap();
+        batch.delete(&unconfigured, "b").await.unwrap();
+        batch.delete(&unreachable, "c").await.unwrap();
+        assert!(batch.failures().is_empty());
+
+        // Each table's writes are sent with its own client, so only the unconfigured one is
+        // missing a region.
+        let failures = batch.flush().await;
+        let missing_region =
+            |failure: &FailedWrite| format!("{:?}", failure.error).contains("Missing Region");
+        assert_eq!(failures.len(), 3);
+        for failure in &failures[..2] {
+            assert_eq!(failure.table_name, "unreachable");
+            assert!(matches!(*failure.error, WriteError::Network(_)));
+            assert!(!missing_region(failure));
+        }
+        assert_eq!(failures[2].table_name, "unconfigured");
+        assert!(missing_region(&failures[2]));
+    }
+
+    #[tokio::test]
+    async fn duplicate_keys_send_the_queued_writes() {
+        let table = ThingTable::new(client(true), "things");
+        let mut batch = BatchWrite::new();
+        batch.delete(&table, "a").await.unwrap();
+        batch.delete(&table, "b").await.unwrap();
+        assert!(batch.failures().is_empty());
+
+        // The queued writes are sent, and fail, before the second write to `a` is queued.
+        assert!(batch.delete(&table, "a").await.is_err());
+        assert_eq!(batch.failures().len(), 2);
+        assert_eq!(batch.pending[0].2.len(), 1);
+
+        // Flushing sends the rest and returns those failures too.
+        let failures = batch.flush().await;
+        assert_eq!(failures.len(), 3);
+    }
+}
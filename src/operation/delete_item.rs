@@ -0,0 +1,118 @@
+use std::marker::PhantomData;
+
+use aws_sdk_dynamodb::operation::RequestId;
+use aws_sdk_dynamodb::{
+    operation::delete_item::builders::DeleteItemFluentBuilder,
+    types::{
+        ConsumedCapacity, ReturnConsumedCapacity, ReturnValue, ReturnValuesOnConditionCheckFailure,
+    },
+};
+
+use crate::{
+    error::WriteError,
+    expr::{self, Condition},
+    item,
+    table::Table,
+};
+
+/// Deletes a single item by its primary key. Returned by the `delete` methods of tables.
+///
+/// Deleting an item that doesn't exist succeeds unless a [`condition`](Self::condition) says
+/// otherwise. If [`return_old`](Self::return_old) is set, the deleted item is read back as `R`.
+#[derive(Debug, Clone)]
+pub struct DeleteItem<T: Table, R = ()> {
+    request: DeleteItemFluentBuilder,
+    condition: Option<Condition>,
+    _table: PhantomData<T>,
+    _return: PhantomData<R>,
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DeleteItemOutput<R> {
+    /// The item that was deleted, if [`DeleteItem::return_old`] was set and there was one.
+    pub old_item: Option<R>,
+    pub consumed_capacity: Option<ConsumedCapacity>,
+    pub request_id: String,
+}
+
+impl<T: Table> DeleteItem<T> {
+    pub(crate) fn new(request: DeleteItemFluentBuilder) -> Self {
+        Self {
+            request,
+            condition: None,
+            _table: PhantomData,
+            _return: PhantomData,
+        }
+    }
+
+    /// Returns the deleted item, if any, in [`DeleteItemOutput::old_item`].
+    pub fn return_old(self) -> DeleteItem<T, T::Item>
+    where
+        T::Item: item::Deserialize,
+    {
+        DeleteItem {
+            request: self.request.return_values(ReturnValue::AllOld),
+            condition: self.condition,
+            _table: PhantomData,
+            _return: PhantomData,
+        }
+    }
+}
+
+impl<T: Table, R: item::Deserialize> DeleteItem<T, R> {
+    /// Only deletes the item if `condition` holds for it. Conditions from repeated calls must all
+    /// hold. If it doesn't, the error carries the existing item; see
+    /// [`WriteError::current_item`].
+    pub fn condition(mut self, condition: Condition) -> Self {
+        self.condition = Some(match self.condition {
+            Some(existing) => existing.and(condition),
+            None => condition,
+        });
+        self
+    }
+
+    /// Asks DynamoDB to report the capacity the delete consumed in
+    /// [`DeleteItemOutput::consumed_capacity`].
+    pub fn return_consumed_capacity(
+        mut self,
+        return_consumed_capacity: ReturnConsumedCapacity,
+    ) -> Self {
+        self.request = self
+            .request
+            .return_consumed_capacity(return_consumed_capacity);
+        self
+    }
+
+    pub async fn send(self) -> Result<DeleteItemOutput<R>, WriteError> {
+        let mut request = self.request;
+        if let Some(condition) = self.condition {
+            let mut placeholders = expr::Placeholders::default();
+            let condition = condition.render(&mut placeholders)?;
+            let (names, values) = placeholders.into_maps();
+            request = request
+                .condition_expression(condition)
+                .return_values_on_condition_check_failure(
+                    ReturnValuesOnConditionCheckFailure::AllOld,
+                )
+                .set_expression_attribute_names(names)
+                .set_expression_attribute_values(values);
+        }
+
+        let result = request.send().await?;
+        let request_id = result
+            .request_id()
+            .unwrap_or("<unknown request ID>")
+            .to_owned();
+        let old_item = result
+            .attributes
+            .map(item::Deserialize::deserialize_owned_from_map)
+            .transpose()?;
+
+        Ok(DeleteItemOutput {
+            old_item,
+            consumed_capacity: result.consumed_capacity,
+            request_id,
+        })
+    }
+}
@@ -1,16 +1,24 @@
-
 use std::marker::PhantomData;
 
-use aws_sdk_dynamodb::{operation::get_item::builders::GetItemFluentBuilder, types::ConsumedCapacity};
 use aws_sdk_dynamodb::operation::RequestId;
+use aws_sdk_dynamodb::{
+    operation::get_item::builders::GetItemFluentBuilder,
+    types::{ConsumedCapacity, ReturnConsumedCapacity},
+};
 
-use crate::{error::{ReadError, SerializeError}, item, table::Table};
+use crate::{error::ReadError, expr, item, table::Table};
 
+use super::Consistency;
 
+/// Reads a single item by its primary key. Returned by the `get` methods of tables.
+///
+/// The item is read as `I`, which is the table's item type unless [`project`](Self::project) picks
+/// another one.
 #[derive(Debug, Clone)]
-pub struct GetItem<T> {
-    request: Result<GetItemFluentBuilder, SerializeError>,
+pub struct GetItem<T: Table, I = <T as Table>::Item> {
+    request: GetItemFluentBuilder,
     _table: PhantomData<T>,
+    _item: PhantomData<I>,
 }
 
 #[derive(Debug, Clone)]
@@ -21,16 +29,63 @@ pub struct GetItemOutput<I> {
     pub request_id: String,
 }
 
-impl<T: Table> GetItem<T> where T::Item: item::Deserialize {
-    pub(crate) fn new(request: Result<GetItemFluentBuilder, SerializeError>) -> Self {
+impl<T: Table> GetItem<T> {
+    pub(crate) fn new(request: GetItemFluentBuilder) -> Self {
         Self {
             request,
             _table: PhantomData,
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T: Table, I: item::Deserialize> GetItem<T, I> {
+    /// Sets whether the read is strongly consistent. Reads are eventually consistent by default.
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.request = self
+            .request
+            .consistent_read(consistency == Consistency::Strong);
+        self
+    }
+
+    /// Asks DynamoDB to report the capacity the read consumed in
+    /// [`GetItemOutput::consumed_capacity`].
+    pub fn return_consumed_capacity(
+        mut self,
+        return_consumed_capacity: ReturnConsumedCapacity,
+    ) -> Self {
+        self.request = self
+            .request
+            .return_consumed_capacity(return_consumed_capacity);
+        self
+    }
+
+    /// Reads the item as `P`, fetching only the attributes `P` reads. `P` is usually a struct
+    /// deriving [`Item`](crate::Item) with a subset of the table item's fields.
+    pub fn project<P: item::Deserialize>(self) -> GetItem<T, P> {
+        let request = match P::attributes() {
+            Some(attributes) if !attributes.is_empty() => {
+                let mut placeholders = expr::Placeholders::default();
+                let projection = expr::projection(attributes, &mut placeholders);
+                let (names, _) = placeholders.into_maps();
+                self.request
+                    .projection_expression(projection)
+                    .set_expression_attribute_names(names)
+            }
+            _ => self
+                .request
+                .set_projection_expression(None)
+                .set_expression_attribute_names(None),
+        };
+        GetItem {
+            request,
+            _table: PhantomData,
+            _item: PhantomData,
         }
     }
 
-    pub async fn send(self) -> Result<GetItemOutput<T::Item>, ReadError> {
-        let result = self.request?.send().await?;
+    pub async fn send(self) -> Result<GetItemOutput<I>, ReadError> {
+        let result = self.request.send().await?;
         let request_id = result
             .request_id()
             .unwrap_or("<unknown request ID>")
@@ -46,4 +101,4 @@ impl<T: Table> GetItem<T> where T::Item: item::Deserialize {
             request_id,
         })
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,159 @@
+//! Conditions on the range key of a query.
+//!
+//! A [`KeyConditionBuilder`] is typed by the range key's [`value::Type`], so it only accepts values
+//! of that type:
+//!
+//! ```compile_fail
+//! use amo::operation::{KeyCondition, KeyConditionBuilder};
+//! use amo::value::S;
+//!
+//! fn condition(key: KeyConditionBuilder<S>) -> KeyCondition<S> {
+//!     key.gt(3_u64)
+//! }
+//! ```
+//!
+//! `begins_with` is only available for string and binary keys.
+
+use std::marker::PhantomData;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{
+    error::SerializeError,
+    expr::Placeholders,
+    table::check_key,
+    value::{self, KeyType, B, S},
+};
+
+/// Builds a condition on a range key holding values of type `K`. Passed to
+/// [`Query::matching_key`](super::Query::matching_key).
+#[derive(Debug)]
+pub struct KeyConditionBuilder<K> {
+    _type: PhantomData<fn() -> K>,
+}
+
+/// A condition on a range key holding values of type `K`, built with a [`KeyConditionBuilder`].
+#[derive(Debug)]
+pub struct KeyCondition<K> {
+    /// A value that failed to serialize is reported when the request is sent.
+    node: Result<Node, SerializeError>,
+    _type: PhantomData<fn() -> K>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Compare(&'static str, AttributeValue),
+    Between(AttributeValue, AttributeValue),
+    BeginsWith(AttributeValue),
+}
+
+impl<K: KeyType> KeyConditionBuilder<K> {
+    pub(crate) fn new() -> Self {
+        Self { _type: PhantomData }
+    }
+
+    /// True if the range key is `value`.
+    pub fn equals(self, value: impl value::Serialize<Type = K>) -> KeyCondition<K> {
+        self.compare("=", value)
+    }
+
+    pub fn lt(self, value: impl value::Serialize<Type = K>) -> KeyCondition<K> {
+        self.compare("<", value)
+    }
+
+    pub fn le(self, value: impl value::Serialize<Type = K>) -> KeyCondition<K> {
+        self.compare("<=", value)
+    }
+
+    pub fn gt(self, value: impl value::Serialize<Type = K>) -> KeyCondition<K> {
+        self.compare(">", value)
+    }
+
+    pub fn ge(self, value: impl value::Serialize<Type = K>) -> KeyCondition<K> {
+        self.compare(">=", value)
+    }
+
+    /// True if the range key is at least `low` and at most `high`.
+    pub fn between(
+        self,
+        low: impl value::Serialize<Type = K>,
+        high: impl value::Serialize<Type = K>,
+    ) -> KeyCondition<K> {
+        let node = low
+            .serialize_owned()
+            .and_then(|low| high.serialize_owned().map(|high| Node::Between(low, high)));
+        KeyCondition::new(node)
+    }
+
+    fn compare(
+        self,
+        operator: &'static str,
+        value: impl value::Serialize<Type = K>,
+    ) -> KeyCondition<K> {
+        KeyCondition::new(value.serialize_owned().map(|v| Node::Compare(operator, v)))
+    }
+
+    fn begins_with_value(self, prefix: impl value::Serialize<Type = K>) -> KeyCondition<K> {
+        KeyCondition::new(prefix.serialize_owned().map(Node::BeginsWith))
+    }
+}
+
+impl KeyConditionBuilder<S> {
+    /// True if the range key starts with `prefix`.
+    pub fn begins_with(self, prefix: impl value::Serialize<Type = S>) -> KeyCondition<S> {
+        self.begins_with_value(prefix)
+    }
+}
+
+impl KeyConditionBuilder<B> {
+    /// True if the range key starts with the bytes of `prefix`.
+    pub fn begins_with(self, prefix: impl value::Serialize<Type = B>) -> KeyCondition<B> {
+        self.begins_with_value(prefix)
+    }
+}
+
+// Not derived, which would require `K: Clone`.
+impl<K> Clone for KeyCondition<K> {
+    fn clone(&self) -> Self {
+        Self::new(self.node.clone())
+    }
+}
+
+impl<K> KeyCondition<K> {
+    fn new(node: Result<Node, SerializeError>) -> Self {
+        Self {
+            node,
+            _type: PhantomData,
+        }
+    }
+
+    /// Forgets the key's type, once it's been checked.
+    pub(crate) fn erase(self) -> KeyCondition<()> {
+        KeyCondition::new(self.node)
+    }
+
+    /// Renders the condition on the key attribute `name`, whose values may be at most `limit`
+    /// bytes long.
+    pub(crate) fn render(
+        self,
+        name: &str,
+        limit: usize,
+        placeholders: &mut Placeholders,
+    ) -> Result<String, SerializeError> {
+        let node = self.node.map_err(|e| e.at_field(name))?;
+        let key = placeholders.name(name);
+        let mut value = |v| {
+            check_key(v, limit)
+                .map(|v| placeholders.value(v))
+                .map_err(|e| e.at_field(name))
+        };
+        Ok(match node {
+            Node::Compare(operator, v) => format!("{key} {operator} {}", value(v)?),
+            Node::Between(low, high) => {
+                let low = value(low)?;
+                format!("{key} BETWEEN {low} AND {}", value(high)?)
+            }
+            Node::BeginsWith(prefix) => format!("begins_with({key}, {})", value(prefix)?),
+        })
+    }
+}
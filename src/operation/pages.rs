@@ -0,0 +1,565 @@
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::num::NonZeroU32;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use aws_sdk_dynamodb::operation::query::builders::QueryFluentBuilder;
+use aws_sdk_dynamodb::operation::scan::builders::ScanFluentBuilder;
+use aws_sdk_dynamodb::operation::RequestId;
+use aws_sdk_dynamodb::types::{AttributeValue, Capacity, ConsumedCapacity, Select};
+use futures_core::Stream;
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+
+use crate::{
+    error::{DeserializeError, ReadError, SerializeError},
+    expr::{self, Condition, Placeholders},
+    item,
+    table::IndexProjection,
+};
+
+/// The key of the last item DynamoDB evaluated, which the next page starts after.
+pub(crate) type StartKey = HashMap<String, AttributeValue>;
+
+/// Fetches the page starting after the given key, or the first page.
+pub(crate) type Fetch =
+    Box<dyn FnMut(Option<StartKey>) -> BoxFuture<'static, Result<RawPage, ReadError>> + Send>;
+
+/// A page as DynamoDB returned it.
+pub(crate) struct RawPage {
+    pub items: Vec<HashMap<String, AttributeValue>>,
+    pub last_evaluated_key: Option<StartKey>,
+    pub consumed_capacity: Option<ConsumedCapacity>,
+    pub request_id: String,
+}
+
+/// A scan or query request builder, which reads one page at a time.
+pub(crate) trait PageRequest: Clone + Send + Sync + 'static {
+    fn set_limit(self, limit: Option<i32>) -> Self;
+    fn projection_expression(self, projection: String) -> Self;
+    fn select(self, select: Select) -> Self;
+    fn filter_expression(self, filter: String) -> Self;
+    fn set_expression_attribute_names(self, names: Option<HashMap<String, String>>) -> Self;
+    fn set_expression_attribute_values(
+        self,
+        values: Option<HashMap<String, AttributeValue>>,
+    ) -> Self;
+    fn send_page(
+        self,
+        start_key: Option<StartKey>,
+    ) -> BoxFuture<'static, Result<RawPage, ReadError>>;
+}
+
+macro_rules! page_request {
+    ($($builder:ty),* $(,)?) => {
+        $(
+            impl PageRequest for $builder {
+                fn set_limit(self, limit: Option<i32>) -> Self {
+                    self.set_limit(limit)
+                }
+
+                fn projection_expression(self, projection: String) -> Self {
+                    self.projection_expression(projection)
+                }
+
+                fn select(self, select: Select) -> Self {
+                    self.select(select)
+                }
+
+                fn filter_expression(self, filter: String) -> Self {
+                    self.filter_expression(filter)
+                }
+
+                fn set_expression_attribute_names(
+                    self,
+                    names: Option<HashMap<String, String>>,
+                ) -> Self {
+                    self.set_expression_attribute_names(names)
+                }
+
+                fn set_expression_attribute_values(
+                    self,
+                    values: Option<HashMap<String, AttributeValue>>,
+                ) -> Self {
+                    self.set_expression_attribute_values(values)
+                }
+
+                fn send_page(
+                    self,
+                    start_key: Option<StartKey>,
+                ) -> BoxFuture<'static, Result<RawPage, ReadError>> {
+                    let request = self.set_exclusive_start_key(start_key);
+                    async move {
+                        let output = request.send().await?;
+                        Ok(RawPage {
+                            request_id: output
+                                .request_id()
+                                .unwrap_or("<unknown request ID>")
+                                .to_owned(),
+                            items: output.items.unwrap_or_default(),
+                            last_evaluated_key: output.last_evaluated_key,
+                            consumed_capacity: output.consumed_capacity,
+                        })
+                    }
+                    .boxed()
+                }
+            }
+        )*
+    };
+}
+
+page_request!(ScanFluentBuilder, QueryFluentBuilder);
+
+/// The options that scans and queries share.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReadOptions {
+    filter: Option<Condition>,
+    projection: Option<Vec<&'static str>>,
+    /// The index read, if any, and the attributes it holds.
+    index: Option<(&'static str, IndexProjection)>,
+    pub limit: Option<usize>,
+    page_size: Option<NonZeroU32>,
+    pub start_key: Option<StartKey>,
+    pub continue_on_invalid_item: bool,
+}
+
+impl ReadOptions {
+    pub fn filter(&mut self, condition: Condition) {
+        self.filter = Some(match self.filter.take() {
+            Some(existing) => existing.and(condition),
+            None => condition,
+        });
+    }
+
+    pub fn page_size(&mut self, page_size: NonZeroU32) {
+        self.page_size = Some(page_size);
+    }
+
+    /// Fetches only the attributes `P` reads.
+    pub fn project<P: item::Deserialize>(&mut self) {
+        self.projection = P::attributes().filter(|a| !a.is_empty());
+    }
+
+    /// Reads the index `name`, which holds the attributes in `projection`. Unless the index holds
+    /// every attribute, only the attributes `I` reads are fetched.
+    pub fn index<I: item::Deserialize>(&mut self, name: &'static str, projection: IndexProjection) {
+        self.index = Some((name, projection));
+        if projection != IndexProjection::All {
+            self.project::<I>();
+        }
+    }
+
+    /// Adds the limit, projection and filter to `request`, followed by the names and values in
+    /// `placeholders`, which may already hold those of other expressions.
+    pub fn apply<R: PageRequest>(
+        &self,
+        request: R,
+        mut placeholders: Placeholders,
+    ) -> Result<R, SerializeError> {
+        // Without a page size, don't read more than the limit needs.
+        let page_size = self
+            .page_size
+            .map(NonZeroU32::get)
+            .or(self.limit.map(|l| l.try_into().unwrap_or(u32::MAX)))
+            .map(|size| size.try_into().unwrap_or(i32::MAX));
+        let mut request = request.set_limit(page_size);
+        if let Some((index, projected)) = &self.index {
+            if let Some(missing) = self
+                .projection
+                .iter()
+                .flatten()
+                .find(|a| !projected.contains(a))
+            {
+                return Err(SerializeError::invalid(format!(
+                    "attribute `{missing}` isn't projected into the index `{index}`"
+                )));
+            }
+            if self.projection.is_none() && *projected != IndexProjection::All {
+                request = request.select(Select::AllProjectedAttributes);
+            }
+        }
+        if let Some(projection) = &self.projection {
+            let projection = expr::projection(projection.iter().copied(), &mut placeholders);
+            request = request.projection_expression(projection);
+        }
+        if let Some(filter) = self.filter.clone() {
+            request = request.filter_expression(filter.render(&mut placeholders)?);
+        }
+        let (names, values) = placeholders.into_maps();
+        Ok(request
+            .set_expression_attribute_names(names)
+            .set_expression_attribute_values(values))
+    }
+}
+
+/// The function that fetches each page of `request`.
+pub(crate) fn fetch<R: PageRequest>(request: R) -> Fetch {
+    Box::new(move |start_key| request.clone().send_page(start_key))
+}
+
+/// The builder methods that scans and queries share. Expects `request`, `options: ReadOptions` and
+/// `items: Option<Items<I>>` fields.
+macro_rules! read_methods {
+    () => {
+        /// Ends the stream after `limit` items.
+        pub fn limit(mut self, limit: usize) -> Self {
+            self.options.limit = Some(limit);
+            self
+        }
+
+        /// Sets how many items DynamoDB evaluates for each page, before applying any filter. By
+        /// default a page holds as many items as fit in 1 MB.
+        pub fn page_size(mut self, page_size: ::std::num::NonZeroU32) -> Self {
+            self.options.page_size(page_size);
+            self
+        }
+
+        /// Only returns items for which `condition` holds. Items are filtered after they're read,
+        /// so filtered-out items still consume capacity. Filters from repeated calls must all
+        /// hold.
+        pub fn filter(mut self, condition: $crate::expr::Condition) -> Self {
+            self.options.filter(condition);
+            self
+        }
+
+        /// Sets whether the reads are strongly consistent. Reads are eventually consistent by
+        /// default. Global secondary indexes only allow eventually consistent reads.
+        pub fn consistency(mut self, consistency: $crate::operation::Consistency) -> Self
+        where
+            T: $crate::table::StrongReads,
+        {
+            self.request = self
+                .request
+                .consistent_read(consistency == $crate::operation::Consistency::Strong);
+            self
+        }
+
+        /// Asks DynamoDB to report the capacity each page consumed. The total is available from
+        /// [`consumed_capacity`](Self::consumed_capacity).
+        pub fn return_consumed_capacity(
+            mut self,
+            return_consumed_capacity: ::aws_sdk_dynamodb::types::ReturnConsumedCapacity,
+        ) -> Self {
+            self.request = self
+                .request
+                .return_consumed_capacity(return_consumed_capacity);
+            self
+        }
+
+        /// Starts reading after `start_key`, a [`Pages::next_start_key`] saved from the same kind of
+        /// read, instead of from the beginning.
+        pub fn start_after(
+            mut self,
+            start_key: ::std::collections::HashMap<
+                String,
+                ::aws_sdk_dynamodb::types::AttributeValue,
+            >,
+        ) -> Self {
+            self.options.start_key = Some(start_key);
+            self
+        }
+
+        /// Reports items that fail to deserialize as errors and keeps reading, instead of ending
+        /// the stream.
+        pub fn continue_on_invalid_item(mut self) -> Self {
+            self.options.continue_on_invalid_item = true;
+            self
+        }
+
+        /// The total capacity consumed so far, if [`return_consumed_capacity`] was set. Once the
+        /// stream has ended, this covers every page read.
+        ///
+        /// [`return_consumed_capacity`]: Self::return_consumed_capacity
+        pub fn consumed_capacity(&self) -> Option<&::aws_sdk_dynamodb::types::ConsumedCapacity> {
+            self.items.as_ref()?.pages.consumed_capacity()
+        }
+    };
+}
+
+pub(crate) use read_methods;
+
+/// One page of a scan or query.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Page<I> {
+    /// The page's items, each of which might have failed to deserialize.
+    pub items: Vec<Result<I, DeserializeError>>,
+    /// Where the next page starts, or `None` if this is the last page.
+    pub last_evaluated_key: Option<HashMap<String, AttributeValue>>,
+    pub consumed_capacity: Option<ConsumedCapacity>,
+    pub request_id: String,
+}
+
+/// A stream of the pages of a scan or query, each fetched once the previous one has been consumed.
+pub struct Pages<I> {
+    fetch: Option<Fetch>,
+    pending: Option<BoxFuture<'static, Result<RawPage, ReadError>>>,
+    start_key: Option<StartKey>,
+    /// An error building the request, reported as the only item of the stream.
+    error: Option<ReadError>,
+    done: bool,
+    consumed_capacity: Option<ConsumedCapacity>,
+    _item: PhantomData<fn() -> I>,
+}
+
+impl<I> Pages<I> {
+    pub(crate) fn new(fetch: Result<Fetch, SerializeError>, start_key: Option<StartKey>) -> Self {
+        let (fetch, error) = match fetch {
+            Ok(fetch) => (Some(fetch), None),
+            Err(e) => (None, Some(e.into())),
+        };
+        Self {
+            fetch,
+            pending: None,
+            start_key,
+            error,
+            done: false,
+            consumed_capacity: None,
+            _item: PhantomData,
+        }
+    }
+
+    /// The total capacity consumed by the pages fetched so far, if capacity reporting was requested.
+    pub fn consumed_capacity(&self) -> Option<&ConsumedCapacity> {
+        self.consumed_capacity.as_ref()
+    }
+
+    /// Where the next page will start, if any pages are left. After an error, this is where the
+    /// failed page started. Pass it to `start_after` on the same kind of scan or query to resume
+    /// reading from there.
+    pub fn next_start_key(&self) -> Option<&HashMap<String, AttributeValue>> {
+        self.start_key.as_ref()
+    }
+
+    /// Whether every page has been fetched. A stream that ended with an error isn't done.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+impl<I: item::Deserialize> Stream for Pages<I> {
+    type Item = Result<Page<I>, ReadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some(e) = this.error.take() {
+            this.done = true;
+            return Poll::Ready(Some(Err(e)));
+        }
+        let Some(fetch) = &mut this.fetch else {
+            return Poll::Ready(None);
+        };
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let pending = this
+            .pending
+            .get_or_insert_with(|| fetch(this.start_key.clone()));
+        let result = match pending.poll_unpin(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        this.pending = None;
+
+        let page = match result {
+            Ok(page) => page,
+            Err(e) => {
+                // Keep the start key, so the failed page can be retried from it.
+                this.fetch = None;
+                return Poll::Ready(Some(Err(e)));
+            }
+        };
+        if let Some(capacity) = &page.consumed_capacity {
+            add_capacity(&mut this.consumed_capacity, capacity);
+        }
+        this.start_key = page.last_evaluated_key.clone();
+        this.done = page.last_evaluated_key.is_none();
+
+        Poll::Ready(Some(Ok(Page {
+            items: page
+                .items
+                .into_iter()
+                .map(I::deserialize_owned_from_map)
+                .collect(),
+            last_evaluated_key: page.last_evaluated_key,
+            consumed_capacity: page.consumed_capacity,
+            request_id: page.request_id,
+        })))
+    }
+}
+
+/// The items of a scan or query, one at a time.
+pub(crate) struct Items<I> {
+    pub pages: Pages<I>,
+    buffer: VecDeque<Result<I, DeserializeError>>,
+    remaining: Option<usize>,
+    continue_on_invalid_item: bool,
+}
+
+// Buffered items are never pinned.
+impl<I> Unpin for Items<I> {}
+
+impl<I> Items<I> {
+    pub fn new(pages: Pages<I>, options: &ReadOptions) -> Self {
+        Self {
+            pages,
+            buffer: VecDeque::new(),
+            remaining: options.limit,
+            continue_on_invalid_item: options.continue_on_invalid_item,
+        }
+    }
+}
+
+impl<I: item::Deserialize> Stream for Items<I> {
+    type Item = Result<I, ReadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.remaining == Some(0) {
+                return Poll::Ready(None);
+            }
+            match this.buffer.pop_front() {
+                Some(Ok(item)) => {
+                    if let Some(remaining) = &mut this.remaining {
+                        *remaining -= 1;
+                    }
+                    return Poll::Ready(Some(Ok(item)));
+                }
+                Some(Err(e)) => {
+                    if !this.continue_on_invalid_item {
+                        this.remaining = Some(0);
+                    }
+                    return Poll::Ready(Some(Err(e.into())));
+                }
+                None => {}
+            }
+
+            match Pin::new(&mut this.pages).poll_next(cx) {
+                Poll::Ready(Some(Ok(page))) => this.buffer.extend(page.items),
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Adds the capacity consumed by one page to the running total.
+pub(crate) fn add_capacity(total: &mut Option<ConsumedCapacity>, page: &ConsumedCapacity) {
+    let total = match total {
+        Some(total) => total,
+        None => {
+            *total = Some(page.clone());
+            return;
+        }
+    };
+
+    fn sum(a: &mut Option<f64>, b: Option<f64>) {
+        *a = match (*a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+    }
+    fn sum_capacity(a: &mut Capacity, b: &Capacity) {
+        sum(&mut a.capacity_units, b.capacity_units);
+        sum(&mut a.read_capacity_units, b.read_capacity_units);
+        sum(&mut a.write_capacity_units, b.write_capacity_units);
+    }
+    fn sum_indexes(
+        a: &mut Option<HashMap<String, Capacity>>,
+        b: &Option<HashMap<String, Capacity>>,
+    ) {
+        let Some(b) = b else { return };
+        let a = a.get_or_insert_with(HashMap::new);
+        for (index, capacity) in b {
+            a.entry(index.clone())
+                .and_modify(|total| sum_capacity(total, capacity))
+                .or_insert_with(|| capacity.clone());
+        }
+    }
+
+    sum(&mut total.capacity_units, page.capacity_units);
+    sum(&mut total.read_capacity_units, page.read_capacity_units);
+    sum(&mut total.write_capacity_units, page.write_capacity_units);
+    match (&mut total.table, &page.table) {
+        (Some(a), Some(b)) => sum_capacity(a, b),
+        (a @ None, b) => *a = b.clone(),
+        (Some(_), None) => {}
+    }
+    sum_indexes(
+        &mut total.local_secondary_indexes,
+        &page.local_secondary_indexes,
+    );
+    sum_indexes(
+        &mut total.global_secondary_indexes,
+        &page.global_secondary_indexes,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures_util::StreamExt;
+
+    use super::*;
+
+    fn key(id: &str) -> StartKey {
+        HashMap::from([("id".to_owned(), AttributeValue::S(id.to_owned()))])
+    }
+
+    fn page(next: Option<&str>) -> RawPage {
+        RawPage {
+            items: vec![HashMap::new()],
+            last_evaluated_key: next.map(key),
+            consumed_capacity: None,
+            request_id: "request".to_owned(),
+        }
+    }
+
+    /// Fetches the pages `a` and `b`, failing the first attempt at `b` if `fail`, and records the
+    /// start key of each attempt.
+    fn flaky(starts: Arc<Mutex<Vec<Option<StartKey>>>>, fail: bool) -> Fetch {
+        let mut failed = !fail;
+        Box::new(move |start_key| {
+            starts.lock().unwrap().push(start_key.clone());
+            let result = match start_key {
+                None => Ok(page(Some("b"))),
+                Some(_) if !failed => {
+                    failed = true;
+                    Err(ReadError::Unprocessed(1))
+                }
+                Some(_) => Ok(page(None)),
+            };
+            async move { result }.boxed()
+        })
+    }
+
+    #[tokio::test]
+    async fn keeps_start_key_after_error() {
+        let starts = Arc::new(Mutex::new(Vec::new()));
+        let mut pages = Pages::<()>::new(Ok(flaky(starts.clone(), true)), None);
+
+        assert!(pages.next().await.unwrap().is_ok());
+        assert!(pages.next().await.unwrap().is_err());
+        assert!(pages.next().await.is_none());
+        assert!(!pages.is_done());
+        assert_eq!(pages.next_start_key(), Some(&key("b")));
+
+        // Resuming from the saved key retries the failed page.
+        let start_key = pages.next_start_key().cloned();
+        let mut pages = Pages::<()>::new(Ok(flaky(starts.clone(), false)), start_key);
+        let page = pages.next().await.unwrap().unwrap();
+        assert_eq!(page.last_evaluated_key, None);
+        assert!(pages.next().await.is_none());
+        assert!(pages.is_done());
+
+        assert_eq!(
+            *starts.lock().unwrap(),
+            [None, Some(key("b")), Some(key("b"))]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use aws_sdk_dynamodb::{operation::query::builders::QueryFluentBuilder, types::AttributeValue};
+use futures_core::Stream;
+
+use crate::{
+    error::{ReadError, SerializeError},
+    expr::Placeholders,
+    item,
+    table::{IndexProjection, KeySchema, RangeKeySchema, Source, MAX_RANGE_KEY_SIZE},
+};
+
+use super::key_condition::{KeyCondition, KeyConditionBuilder};
+use super::pages::{self, read_methods, Fetch, Items, Pages, ReadOptions};
+
+/// Reads the items with one hash key, in range key order if there is a range key. Returned by the
+/// `query` methods of tables with a range key and of [`IndexHandle`](crate::table::IndexHandle).
+///
+/// Like a [`Scan`](super::Scan), a `Query` is a [`Stream`] of items, read as `I`, which fetches
+/// pages as it goes. [`matching_key`](Self::matching_key) narrows it to the range keys matching a
+/// condition, which only accepts values of the range key's type.
+///
+/// The hash key and the key condition are checked when the first page is fetched, since the
+/// condition can still change until then. If either is invalid, the stream's only item is the
+/// [`ReadError::Serialize`] saying which.
+///
+/// By default the stream ends after the first item that fails to deserialize. With
+/// [`continue_on_invalid_item`](Self::continue_on_invalid_item), each such item is reported as an
+/// error and the query carries on.
+pub struct Query<T: KeySchema, I = <T as Source>::Item> {
+    request: QueryFluentBuilder,
+    hash: Result<AttributeValue, SerializeError>,
+    /// The range key attribute and the condition on it.
+    range: Option<(&'static str, KeyCondition<()>)>,
+    options: ReadOptions,
+    items: Option<Items<I>>,
+    _table: PhantomData<fn() -> T>,
+}
+
+impl<T: KeySchema> Query<T> {
+    pub(crate) fn new(
+        request: QueryFluentBuilder,
+        hash: Result<AttributeValue, SerializeError>,
+    ) -> Self {
+        Self {
+            request,
+            hash,
+            range: None,
+            options: ReadOptions::default(),
+            items: None,
+            _table: PhantomData,
+        }
+    }
+}
+
+impl<T: RangeKeySchema, I: item::Deserialize> Query<T, I> {
+    /// Only reads items whose range key matches the condition built by `condition`. A later call
+    /// replaces an earlier one.
+    pub fn matching_key(
+        mut self,
+        condition: impl FnOnce(KeyConditionBuilder<T::RangeKeyType>) -> KeyCondition<T::RangeKeyType>,
+    ) -> Self {
+        let condition = condition(KeyConditionBuilder::new());
+        self.range = Some((T::RANGE_KEY_ATTRIBUTE, condition.erase()));
+        self
+    }
+
+    /// Returns items in ascending range key order. This is the default.
+    pub fn ascending(mut self) -> Self {
+        self.request = self.request.scan_index_forward(true);
+        self
+    }
+
+    /// Returns items in descending range key order.
+    pub fn descending(mut self) -> Self {
+        self.request = self.request.scan_index_forward(false);
+        self
+    }
+}
+
+impl<T: KeySchema, I: item::Deserialize> Query<T, I> {
+    read_methods!();
+
+    /// Reads the index `name`, which holds the attributes in `projection`.
+    pub(crate) fn in_index(mut self, name: &'static str, projection: IndexProjection) -> Self {
+        self.options.index::<I>(name, projection);
+        self
+    }
+
+    /// Reads items as `P`, fetching only the attributes `P` reads.
+    pub fn project<P: item::Deserialize>(mut self) -> Query<T, P> {
+        self.options.project::<P>();
+        Query {
+            request: self.request,
+            hash: self.hash,
+            range: self.range,
+            options: self.options,
+            items: None,
+            _table: PhantomData,
+        }
+    }
+
+    /// The query's pages rather than its items. [`limit`](Self::limit) and
+    /// [`continue_on_invalid_item`](Self::continue_on_invalid_item) don't apply to pages.
+    pub fn pages(self) -> Pages<I> {
+        Pages::new(self.fetch(), self.options.start_key.clone())
+    }
+
+    /// Builds the function that fetches each page of the query.
+    fn fetch(&self) -> Result<Fetch, SerializeError> {
+        let mut placeholders = Placeholders::default();
+        let hash = self.hash.clone()?;
+        let mut key_condition = format!(
+            "{} = {}",
+            placeholders.name(T::HASH_KEY_ATTRIBUTE),
+            placeholders.value(hash)
+        );
+        if let Some((attribute, range)) = self.range.clone() {
+            let range = range.render(attribute, MAX_RANGE_KEY_SIZE, &mut placeholders)?;
+            key_condition = format!("{key_condition} AND {range}");
+        }
+        let request = self.request.clone().key_condition_expression(key_condition);
+        let request = self.options.apply(request, placeholders)?;
+        Ok(pages::fetch(request))
+    }
+}
+
+// Items are never pinned, only buffered.
+impl<T: KeySchema, I> Unpin for Query<T, I> {}
+
+impl<T: KeySchema, I: item::Deserialize> Stream for Query<T, I> {
+    type Item = Result<I, ReadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let items = match &mut this.items {
+            Some(items) => items,
+            None => {
+                let pages = Pages::new(this.fetch(), this.options.start_key.clone());
+                this.items.insert(Items::new(pages, &this.options))
+            }
+        };
+        Pin::new(items).poll_next(cx)
+    }
+}
@@ -0,0 +1,112 @@
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use aws_sdk_dynamodb::operation::scan::builders::ScanFluentBuilder;
+use futures_core::Stream;
+
+use crate::{
+    error::{ReadError, SerializeError},
+    expr::Placeholders,
+    item,
+    table::{IndexProjection, Source},
+};
+
+use super::pages::{self, read_methods, Fetch, Items, Pages, ReadOptions};
+
+mod parallel;
+
+pub use parallel::{ParallelScan, SegmentCursor};
+
+/// Reads every item in a table or index. Returned by [`Table::all`](crate::table::Table::all) and
+/// [`IndexHandle::all`](crate::table::IndexHandle::all).
+///
+/// A `Scan` is a [`Stream`] of items, read as `I`, which fetches pages as it goes and follows
+/// DynamoDB's `LastEvaluatedKey` until the table is exhausted. Configure it before polling it; use
+/// [`pages`](Self::pages) for page-level access.
+///
+/// By default the stream ends after the first item that fails to deserialize. With
+/// [`continue_on_invalid_item`](Self::continue_on_invalid_item), each such item is reported as an
+/// error and the scan carries on.
+pub struct Scan<T: Source, I = <T as Source>::Item> {
+    request: ScanFluentBuilder,
+    options: ReadOptions,
+    items: Option<Items<I>>,
+    _table: PhantomData<fn() -> T>,
+}
+
+impl<T: Source> Scan<T> {
+    pub(crate) fn new(request: ScanFluentBuilder) -> Self {
+        Self {
+            request,
+            options: ReadOptions::default(),
+            items: None,
+            _table: PhantomData,
+        }
+    }
+}
+
+impl<T: Source, I: item::Deserialize> Scan<T, I> {
+    read_methods!();
+
+    /// Reads the index `name`, which holds the attributes in `projection`.
+    pub(crate) fn in_index(mut self, name: &'static str, projection: IndexProjection) -> Self {
+        self.options.index::<I>(name, projection);
+        self
+    }
+
+    /// Reads items as `P`, fetching only the attributes `P` reads.
+    pub fn project<P: item::Deserialize>(mut self) -> Scan<T, P> {
+        self.options.project::<P>();
+        Scan {
+            request: self.request,
+            options: self.options,
+            items: None,
+            _table: PhantomData,
+        }
+    }
+
+    /// Splits the scan into `segments` segments which are scanned concurrently, and merges their
+    /// items into one stream. See [`ParallelScan`]. [`start_after`](Self::start_after) doesn't apply
+    /// to parallel scans, which resume from [`ParallelScan::resume`] instead.
+    pub fn parallel(self, segments: u32) -> ParallelScan<T, I> {
+        ParallelScan::new(self, segments)
+    }
+
+    /// The scan's pages rather than its items. [`limit`](Self::limit) and
+    /// [`continue_on_invalid_item`](Self::continue_on_invalid_item) don't apply to pages.
+    pub fn pages(self) -> Pages<I> {
+        Pages::new(self.fetch(None), self.options.start_key.clone())
+    }
+
+    /// Builds the function that fetches each page of the scan, or of one `(segment, total)`.
+    fn fetch(&self, segment: Option<(u32, u32)>) -> Result<Fetch, SerializeError> {
+        let mut request = self.request.clone();
+        if let Some((segment, total)) = segment {
+            request = request
+                .segment(segment.try_into().unwrap_or(i32::MAX))
+                .total_segments(total.try_into().unwrap_or(i32::MAX));
+        }
+        let request = self.options.apply(request, Placeholders::default())?;
+        Ok(pages::fetch(request))
+    }
+}
+
+// Items are never pinned, only buffered.
+impl<T: Source, I> Unpin for Scan<T, I> {}
+
+impl<T: Source, I: item::Deserialize> Stream for Scan<T, I> {
+    type Item = Result<I, ReadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let items = match &mut this.items {
+            Some(items) => items,
+            None => {
+                let pages = Pages::new(this.fetch(None), this.options.start_key.clone());
+                this.items.insert(Items::new(pages, &this.options))
+            }
+        };
+        Pin::new(items).poll_next(cx)
+    }
+}
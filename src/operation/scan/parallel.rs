@@ -0,0 +1,417 @@
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use aws_sdk_dynamodb::types::{AttributeValue, ConsumedCapacity};
+use futures_core::Stream;
+
+use crate::{
+    error::{DeserializeError, ReadError, SerializeError},
+    item,
+    table::Source,
+};
+
+use super::super::pages::{add_capacity, Fetch, Pages, StartKey};
+use super::Scan;
+
+/// A [`Scan`] split into segments that are read concurrently. Returned by [`Scan::parallel`].
+///
+/// Like a `Scan`, a `ParallelScan` is a [`Stream`] of items. Items arrive in no particular order.
+/// At most [`concurrency`](Self::concurrency) segments are read at once, and the rest start as
+/// earlier ones finish.
+///
+/// [`cursors`](Self::cursors) records how far each segment has got, so a scan that stops partway
+/// can be restarted with [`resume`](Self::resume). A cursor only moves past a page once every item
+/// on it has been returned, so a resumed scan may repeat items but never skips them.
+pub struct ParallelScan<T: Source, I = <T as Source>::Item> {
+    scan: Scan<T, I>,
+    total_segments: u32,
+    concurrency: usize,
+    cursors: Vec<SegmentCursor>,
+    segments: Option<Vec<Segment<I>>>,
+    remaining: Option<usize>,
+    /// The active segment polled first next time, so that one busy segment can't starve the rest.
+    next: usize,
+}
+
+/// How far one segment of a [`ParallelScan`] has got.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentCursor {
+    segment: u32,
+    total_segments: u32,
+    start_key: Option<HashMap<String, AttributeValue>>,
+    done: bool,
+}
+
+impl SegmentCursor {
+    pub fn segment(&self) -> u32 {
+        self.segment
+    }
+
+    pub fn total_segments(&self) -> u32 {
+        self.total_segments
+    }
+
+    /// The key the segment restarts after, or `None` to restart it from its beginning.
+    pub fn start_key(&self) -> Option<&HashMap<String, AttributeValue>> {
+        self.start_key.as_ref()
+    }
+
+    /// Whether every item in the segment has been returned.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// A cursor for `segment` of `total_segments` that restarts after `start_key`, for restoring a
+    /// cursor saved elsewhere.
+    pub fn new(
+        segment: u32,
+        total_segments: u32,
+        start_key: Option<HashMap<String, AttributeValue>>,
+        done: bool,
+    ) -> Self {
+        Self {
+            segment,
+            total_segments,
+            start_key,
+            done,
+        }
+    }
+}
+
+struct Segment<I> {
+    pages: Pages<I>,
+    buffer: VecDeque<Result<I, DeserializeError>>,
+    /// Where the page after the buffered one starts.
+    next_start_key: Option<StartKey>,
+    /// Whether the segment has ended, successfully or not.
+    ended: bool,
+}
+
+impl<T: Source, I: item::Deserialize> ParallelScan<T, I> {
+    pub(super) fn new(scan: Scan<T, I>, total_segments: u32) -> Self {
+        let total_segments = total_segments.max(1);
+        Self {
+            remaining: scan.options.limit,
+            scan,
+            total_segments,
+            concurrency: total_segments as usize,
+            cursors: (0..total_segments)
+                .map(|segment| SegmentCursor::new(segment, total_segments, None, false))
+                .collect(),
+            segments: None,
+            next: 0,
+        }
+    }
+
+    /// Sets how many segments are read at once. By default every segment is.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Restarts each segment from a cursor saved from an earlier scan with the same number of
+    /// segments. Segments without a cursor start from their beginning.
+    pub fn resume(mut self, cursors: impl IntoIterator<Item = SegmentCursor>) -> Self {
+        for cursor in cursors {
+            if cursor.total_segments != self.total_segments {
+                continue;
+            }
+            if let Some(slot) = self.cursors.get_mut(cursor.segment as usize) {
+                *slot = cursor;
+            }
+        }
+        self
+    }
+
+    /// How far each segment has got, in segment order.
+    pub fn cursors(&self) -> &[SegmentCursor] {
+        &self.cursors
+    }
+
+    /// The total capacity consumed so far by every segment, if
+    /// [`Scan::return_consumed_capacity`] was set.
+    pub fn consumed_capacity(&self) -> Option<ConsumedCapacity> {
+        let mut total = None;
+        for segment in self.segments.iter().flatten() {
+            if let Some(capacity) = segment.pages.consumed_capacity() {
+                add_capacity(&mut total, capacity);
+            }
+        }
+        total
+    }
+
+    /// Starts a segment for each cursor, fetching segment `n`'s pages with `fetch(n)`.
+    fn start(
+        &self,
+        mut fetch: impl FnMut(u32) -> Result<Fetch, SerializeError>,
+    ) -> Vec<Segment<I>> {
+        self.cursors
+            .iter()
+            .map(|cursor| Segment {
+                pages: Pages::new(fetch(cursor.segment), cursor.start_key.clone()),
+                buffer: VecDeque::new(),
+                next_start_key: None,
+                ended: cursor.done,
+            })
+            .collect()
+    }
+}
+
+// Items are never pinned, only buffered.
+impl<T: Source, I> Unpin for ParallelScan<T, I> {}
+
+impl<T: Source, I: item::Deserialize> Stream for ParallelScan<T, I> {
+    type Item = Result<I, ReadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.segments.is_none() {
+            let total = this.total_segments;
+            this.segments = Some(this.start(|segment| this.scan.fetch(Some((segment, total)))));
+        }
+        let segments = this.segments.as_mut().expect("segments were just started");
+
+        let active: Vec<usize> = (0..segments.len())
+            .filter(|&i| !segments[i].ended)
+            .take(this.concurrency)
+            .collect();
+        if active.is_empty() || this.remaining == Some(0) {
+            return Poll::Ready(None);
+        }
+
+        for offset in 0..active.len() {
+            let i = active[(this.next + offset) % active.len()];
+            let segment = &mut segments[i];
+            let cursor = &mut this.cursors[i];
+            loop {
+                if let Some(item) = segment.buffer.pop_front() {
+                    if segment.buffer.is_empty() {
+                        cursor.start_key = segment.next_start_key.take();
+                        cursor.done = cursor.start_key.is_none();
+                        segment.ended = cursor.done;
+                    }
+                    this.next = (this.next + offset + 1) % active.len();
+                    return Poll::Ready(Some(match item {
+                        Ok(item) => {
+                            if let Some(remaining) = &mut this.remaining {
+                                *remaining -= 1;
+                            }
+                            Ok(item)
+                        }
+                        Err(e) => {
+                            if !this.scan.options.continue_on_invalid_item {
+                                this.remaining = Some(0);
+                            }
+                            Err(e.into())
+                        }
+                    }));
+                }
+
+                match Pin::new(&mut segment.pages).poll_next(cx) {
+                    Poll::Ready(Some(Ok(page))) => {
+                        if page.items.is_empty() {
+                            cursor.start_key = page.last_evaluated_key;
+                            cursor.done = cursor.start_key.is_none();
+                            segment.ended = cursor.done;
+                            if segment.ended {
+                                break;
+                            }
+                        } else {
+                            segment.buffer.extend(page.items);
+                            segment.next_start_key = page.last_evaluated_key;
+                        }
+                    }
+                    Poll::Ready(Some(Err(e))) => {
+                        // The cursor still points at the page that failed, so resuming retries it.
+                        segment.ended = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                    Poll::Ready(None) => {
+                        segment.ended = true;
+                        break;
+                    }
+                    Poll::Pending => break,
+                }
+            }
+        }
+
+        if segments.iter().all(|s| s.ended) {
+            Poll::Ready(None)
+        } else {
+            // Segments that just ended may have let waiting ones become active.
+            if active.iter().any(|&i| segments[i].ended) {
+                cx.waker().wake_by_ref();
+            }
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures_util::{FutureExt, StreamExt};
+
+    use super::super::super::pages::RawPage;
+    use super::*;
+    use crate::table::Table;
+    use crate::test_support::{client, Thing, ThingTable};
+
+    type Calls = Arc<Mutex<Vec<(u32, Option<StartKey>)>>>;
+
+    /// The key a segment's page `page` starts after.
+    fn key(segment: u32, page: usize) -> StartKey {
+        HashMap::from([(
+            "id".to_owned(),
+            AttributeValue::S(format!("{segment}/{page}")),
+        )])
+    }
+
+    /// Fetches two pages of two items for `segment`, failing the second page the first time it's
+    /// asked for if `fail`, and records the start key of each attempt.
+    fn fake(segment: u32, calls: Calls, fail: bool) -> Fetch {
+        let mut failed = !fail;
+        Box::new(move |start_key| {
+            calls.lock().unwrap().push((segment, start_key.clone()));
+            let page = match start_key {
+                None => 0,
+                Some(_) if !failed => {
+                    failed = true;
+                    return async { Err(ReadError::Unprocessed(2)) }.boxed();
+                }
+                Some(_) => 1,
+            };
+            let items = (0..2)
+                .map(|i| {
+                    let id = format!("{segment}/{page}/{i}");
+                    HashMap::from([("id".to_owned(), AttributeValue::S(id))])
+                })
+                .collect();
+            let result = Ok(RawPage {
+                items,
+                last_evaluated_key: (page == 0).then(|| key(segment, 1)),
+                consumed_capacity: None,
+                request_id: "request".to_owned(),
+            });
+            async move { result }.boxed()
+        })
+    }
+
+    /// Reads `scan`'s segments with [`fake`], failing those in `failing`.
+    fn faked(
+        mut scan: ParallelScan<ThingTable>,
+        calls: &Calls,
+        failing: &[u32],
+    ) -> ParallelScan<ThingTable> {
+        let segments =
+            scan.start(|segment| Ok(fake(segment, calls.clone(), failing.contains(&segment))));
+        scan.segments = Some(segments);
+        scan
+    }
+
+    fn ids(items: Vec<Result<Thing, ReadError>>) -> Vec<String> {
+        let mut ids: Vec<_> = items.into_iter().map(|item| item.unwrap().id).collect();
+        ids.sort();
+        ids
+    }
+
+    fn table() -> ThingTable {
+        ThingTable::new(client(), "things")
+    }
+
+    #[tokio::test]
+    async fn reads_every_segment_a_few_at_a_time() {
+        let calls = Calls::default();
+        let mut scan = faked(table().all().parallel(3).concurrency(2), &calls, &[]);
+        let items = scan.by_ref().collect::<Vec<_>>().await;
+
+        let expected: Vec<_> = (0..3)
+            .flat_map(|s| (0..2).flat_map(move |p| (0..2).map(move |i| format!("{s}/{p}/{i}"))))
+            .collect();
+        assert_eq!(ids(items), expected);
+        assert!(scan.cursors().iter().all(SegmentCursor::is_done));
+        assert!(scan.cursors().iter().all(|c| c.start_key().is_none()));
+
+        // Segment 2 only starts once one of the first two has finished.
+        let calls = calls.lock().unwrap();
+        let first = |segment| calls.iter().position(|(s, _)| *s == segment).unwrap();
+        let last = |segment| calls.iter().rposition(|(s, _)| *s == segment).unwrap();
+        assert!(first(2) > last(0).min(last(1)));
+        assert_eq!(calls.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn resumes_each_segment_from_its_cursor() {
+        let calls = Calls::default();
+        let scan = table().all().parallel(3).resume([
+            SegmentCursor::new(0, 3, None, true),
+            SegmentCursor::new(1, 3, Some(key(1, 1)), false),
+        ]);
+        let items = faked(scan, &calls, &[]).collect::<Vec<_>>().await;
+
+        assert_eq!(
+            ids(items),
+            ["1/1/0", "1/1/1", "2/0/0", "2/0/1", "2/1/0", "2/1/1"]
+        );
+        let mut calls = calls.lock().unwrap().clone();
+        calls.sort_by_key(|(segment, _)| *segment);
+        assert_eq!(
+            calls,
+            [(1, Some(key(1, 1))), (2, None), (2, Some(key(2, 1)))]
+        );
+    }
+
+    #[tokio::test]
+    async fn cursor_moves_past_a_page_once_it_is_returned() {
+        let calls = Calls::default();
+        let mut scan = faked(table().all().parallel(2).concurrency(1), &calls, &[0]);
+
+        assert_eq!(scan.next().await.unwrap().unwrap().id, "0/0/0");
+        assert_eq!(scan.cursors()[0].start_key(), None);
+        assert_eq!(scan.next().await.unwrap().unwrap().id, "0/0/1");
+        assert_eq!(scan.cursors()[0].start_key(), Some(&key(0, 1)));
+
+        // A failed page ends its segment, but not the others.
+        assert!(matches!(
+            scan.next().await,
+            Some(Err(ReadError::Unprocessed(2)))
+        ));
+        let items = scan.by_ref().collect::<Vec<_>>().await;
+        assert_eq!(ids(items), ["1/0/0", "1/0/1", "1/1/0", "1/1/1"]);
+
+        // The failed segment's cursor still points at the page that failed.
+        let cursors = scan.cursors().to_vec();
+        assert_eq!(
+            cursors,
+            [
+                SegmentCursor::new(0, 2, Some(key(0, 1)), false),
+                SegmentCursor::new(1, 2, None, true),
+            ]
+        );
+        let scan = table().all().parallel(2).resume(cursors);
+        let items = faked(scan, &calls, &[]).collect::<Vec<_>>().await;
+        assert_eq!(ids(items), ["0/1/0", "0/1/1"]);
+    }
+
+    #[test]
+    fn cursors_for_other_segment_counts_are_ignored() {
+        let scan = table().all().parallel(2).resume([
+            SegmentCursor::new(0, 3, Some(key(0, 1)), false),
+            SegmentCursor::new(2, 2, None, true),
+            SegmentCursor::new(1, 2, None, true),
+        ]);
+        assert_eq!(
+            scan.cursors(),
+            [
+                SegmentCursor::new(0, 2, None, false),
+                SegmentCursor::new(1, 2, None, true),
+            ]
+        );
+
+        let scan = table().all().parallel(0).concurrency(0);
+        assert_eq!(scan.cursors(), [SegmentCursor::new(0, 1, None, false)]);
+        assert_eq!(scan.concurrency, 1);
+    }
+}
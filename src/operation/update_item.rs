@@ -0,0 +1,451 @@
+use std::marker::PhantomData;
+
+use aws_sdk_dynamodb::operation::RequestId;
+use aws_sdk_dynamodb::{
+    operation::update_item::builders::UpdateItemFluentBuilder,
+    types::{
+        AttributeValue, ConsumedCapacity, ReturnConsumedCapacity, ReturnValue,
+        ReturnValuesOnConditionCheckFailure,
+    },
+};
+
+use crate::{
+    error::{SerializeError, WriteError},
+    expr::{self, Attr, Condition, Path},
+    item,
+    table::Table,
+    value::{self, SetType, Type, L, N},
+};
+
+/// Changes some attributes of an item, creating it if it doesn't exist. Returned by the `update`
+/// methods of tables.
+///
+/// Each action takes an [`Attr`], and only accepts values of the attribute's type. If
+/// [`return_values`](Self::return_values) is set, the returned attributes are read as `R`.
+///
+/// Actions chain, so a value that fails to serialize isn't reported by its action. The first such
+/// error, naming the attribute, is returned by [`send`](Self::send) without sending anything.
+#[derive(Debug, Clone)]
+pub struct UpdateItem<T: Table, R = ()> {
+    request: UpdateItemFluentBuilder,
+    actions: Vec<Action>,
+    error: Option<SerializeError>,
+    condition: Option<Condition>,
+    _table: PhantomData<T>,
+    _return: PhantomData<R>,
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+    Set(Path, SetValue),
+    Remove(Path),
+    Add(Path, AttributeValue),
+    Delete(Path, AttributeValue),
+}
+
+#[derive(Debug, Clone)]
+enum SetValue {
+    Value(AttributeValue),
+    IfNotExists(AttributeValue),
+    ListAppend(AttributeValue),
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct UpdateItemOutput<R> {
+    /// The attributes selected by [`UpdateItem::return_values`], if any.
+    pub item: Option<R>,
+    pub consumed_capacity: Option<ConsumedCapacity>,
+    pub request_id: String,
+}
+
+impl<T: Table> UpdateItem<T> {
+    pub(crate) fn new(request: UpdateItemFluentBuilder) -> Self {
+        Self {
+            request,
+            actions: Vec::new(),
+            error: None,
+            condition: None,
+            _table: PhantomData,
+            _return: PhantomData,
+        }
+    }
+}
+
+impl<T: Table, R> UpdateItem<T, R> {
+    /// Returns the whole item as it was before the update in [`UpdateItemOutput::item`].
+    pub fn return_old(self) -> UpdateItem<T, T::Item>
+    where
+        T::Item: item::Deserialize,
+    {
+        self.return_values(ReturnValue::AllOld)
+    }
+
+    /// Returns the whole item as it is after the update in [`UpdateItemOutput::item`].
+    pub fn return_new(self) -> UpdateItem<T, T::Item>
+    where
+        T::Item: item::Deserialize,
+    {
+        self.return_values(ReturnValue::AllNew)
+    }
+
+    /// Returns the attributes selected by `return_values` in [`UpdateItemOutput::item`], read as
+    /// `P`. `UPDATED_OLD` and `UPDATED_NEW` only return the updated attributes, so `P` is usually a
+    /// projection type with just those fields.
+    pub fn return_values<P: item::Deserialize>(
+        self,
+        return_values: ReturnValue,
+    ) -> UpdateItem<T, P> {
+        UpdateItem {
+            request: self.request.return_values(return_values),
+            actions: self.actions,
+            error: self.error,
+            condition: self.condition,
+            _table: PhantomData,
+            _return: PhantomData,
+        }
+    }
+}
+
+impl<T: Table, R: item::Deserialize> UpdateItem<T, R> {
+    /// Sets `attr` to `value`, written with the item type's [`POLICY`](item::Serialize::POLICY)
+    /// as a put would write it. Values the policy omits from items, such as `None` and empty sets
+    /// by default, remove the attribute instead.
+    pub fn set<V: Type>(self, attr: &Attr<V>, value: impl value::Serialize<Type = V>) -> Self
+    where
+        T::Item: item::Serialize,
+    {
+        match value
+            .serialize_owned_attribute(&<T::Item as item::Serialize>::POLICY)
+            .transpose()
+        {
+            Some(value) => self.push(attr, value, |path, v| Action::Set(path, SetValue::Value(v))),
+            None => self.remove(attr),
+        }
+    }
+
+    /// Sets `attr` to `value` unless the item already has it.
+    pub fn set_if_not_exists<V: Type>(
+        self,
+        attr: &Attr<V>,
+        value: impl value::Serialize<Type = V>,
+    ) -> Self {
+        self.push(attr, value.serialize_owned(), |path, v| {
+            Action::Set(path, SetValue::IfNotExists(v))
+        })
+    }
+
+    /// Adds `by` to the number in `attr`, treating a missing attribute as zero. Decrement with a
+    /// negative `by`.
+    pub fn increment(self, attr: &Attr<N>, by: impl value::Serialize<Type = N>) -> Self {
+        self.push(attr, by.serialize_owned(), Action::Add)
+    }
+
+    /// Appends `values` to the list in `attr`, treating a missing attribute as an empty list.
+    pub fn list_append(self, attr: &Attr<L>, values: impl value::Serialize<Type = L>) -> Self {
+        self.push(attr, values.serialize_owned(), |path, v| {
+            Action::Set(path, SetValue::ListAppend(v))
+        })
+    }
+
+    /// Adds `values` to the set in `attr`, creating the set if the item doesn't have it.
+    pub fn add_to_set<V: SetType>(
+        self,
+        attr: &Attr<V>,
+        values: impl value::Serialize<Type = V>,
+    ) -> Self {
+        self.push(attr, values.serialize_owned(), Action::Add)
+    }
+
+    /// Removes `values` from the set in `attr`. DynamoDB removes the attribute if the set ends up
+    /// empty.
+    pub fn delete_from_set<V: SetType>(
+        self,
+        attr: &Attr<V>,
+        values: impl value::Serialize<Type = V>,
+    ) -> Self {
+        self.push(attr, values.serialize_owned(), Action::Delete)
+    }
+
+    /// Removes `attr` from the item.
+    pub fn remove<V: Type>(mut self, attr: &Attr<V>) -> Self {
+        self.actions.push(Action::Remove(attr.to_path()));
+        self
+    }
+
+    /// Only updates the item if `condition` holds for the existing item. Conditions from repeated
+    /// calls must all hold. If it doesn't, the error carries the existing item; see
+    /// [`WriteError::current_item`].
+    pub fn condition(mut self, condition: Condition) -> Self {
+        self.condition = Some(match self.condition {
+            Some(existing) => existing.and(condition),
+            None => condition,
+        });
+        self
+    }
+
+    /// Asks DynamoDB to report the capacity the update consumed in
+    /// [`UpdateItemOutput::consumed_capacity`].
+    pub fn return_consumed_capacity(
+        mut self,
+        return_consumed_capacity: ReturnConsumedCapacity,
+    ) -> Self {
+        self.request = self
+            .request
+            .return_consumed_capacity(return_consumed_capacity);
+        self
+    }
+
+    fn push<V: Type>(
+        mut self,
+        attr: &Attr<V>,
+        value: Result<AttributeValue, SerializeError>,
+        action: impl FnOnce(Path, AttributeValue) -> Action,
+    ) -> Self {
+        match value {
+            Ok(value) => self.actions.push(action(attr.to_path(), value)),
+            Err(e) => {
+                self.error.get_or_insert(attr.to_path().wrap(e));
+            }
+        }
+        self
+    }
+
+    pub async fn send(self) -> Result<UpdateItemOutput<R>, WriteError> {
+        if let Some(error) = self.error {
+            return Err(error.into());
+        }
+        let mut request = self.request;
+        let mut placeholders = expr::Placeholders::default();
+        if let Some(update) = render(self.actions, &mut placeholders) {
+            request = request.update_expression(update);
+        }
+        if let Some(condition) = self.condition {
+            request = request
+                .condition_expression(condition.render(&mut placeholders)?)
+                .return_values_on_condition_check_failure(
+                    ReturnValuesOnConditionCheckFailure::AllOld,
+                );
+        }
+        let (names, values) = placeholders.into_maps();
+        request = request
+            .set_expression_attribute_names(names)
+            .set_expression_attribute_values(values);
+
+        let result = request.send().await?;
+        let request_id = result
+            .request_id()
+            .unwrap_or("<unknown request ID>")
+            .to_owned();
+        let item = result
+            .attributes
+            .map(item::Deserialize::deserialize_owned_from_map)
+            .transpose()?;
+
+        Ok(UpdateItemOutput {
+            item,
+            consumed_capacity: result.consumed_capacity,
+            request_id,
+        })
+    }
+}
+
+/// Renders an `UpdateExpression` performing `actions`, or `None` if there are none.
+fn render(actions: Vec<Action>, placeholders: &mut expr::Placeholders) -> Option<String> {
+    let (mut set, mut remove, mut add, mut delete) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for action in actions {
+        match action {
+            Action::Set(path, value) => {
+                let name = path.render(placeholders);
+                let value = match value {
+                    SetValue::Value(v) => placeholders.value(v),
+                    SetValue::IfNotExists(v) => {
+                        format!("if_not_exists({name}, {})", placeholders.value(v))
+                    }
+                    SetValue::ListAppend(v) => {
+                        let empty = placeholders.value(AttributeValue::L(Vec::new()));
+                        format!(
+                            "list_append(if_not_exists({name}, {empty}), {})",
+                            placeholders.value(v)
+                        )
+                    }
+                };
+                set.push(format!("{name} = {value}"));
+            }
+            Action::Remove(path) => remove.push(path.render(placeholders)),
+            Action::Add(path, v) => add.push(format!(
+                "{} {}",
+                path.render(placeholders),
+                placeholders.value(v)
+            )),
+            Action::Delete(path, v) => delete.push(format!(
+                "{} {}",
+                path.render(placeholders),
+                placeholders.value(v)
+            )),
+        }
+    }
+
+    let clauses: Vec<_> = [
+        ("SET", set),
+        ("REMOVE", remove),
+        ("ADD", add),
+        ("DELETE", delete),
+    ]
+    .into_iter()
+    .filter(|(_, parts)| !parts.is_empty())
+    .map(|(keyword, parts)| format!("{keyword} {}", parts.join(", ")))
+    .collect();
+    (!clauses.is_empty()).then(|| clauses.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::error::SerializeErrorKind;
+    use crate::test_support::{client, ThingTable};
+    use crate::value::{EmptySets, Nones, Optional, Policy, M, S, SS};
+
+    use super::*;
+
+    fn update() -> UpdateItem<ThingTable> {
+        ThingTable::new(client(), "things").update("a").unwrap()
+    }
+
+    fn rendered<T: Table>(update: UpdateItem<T>) -> (String, Vec<(String, String)>) {
+        let mut placeholders = expr::Placeholders::default();
+        let expression = render(update.actions, &mut placeholders).unwrap();
+        let (names, _) = placeholders.into_maps();
+        let mut names: Vec<_> = names.unwrap().into_iter().collect();
+        names.sort();
+        (expression, names)
+    }
+
+    #[test]
+    fn set_none_removes() {
+        let name = Attr::<S>::new("name");
+        let nickname = Attr::<Optional<S>>::new("nickname");
+        let title = Attr::<Optional<S>>::new("title");
+        let update = update()
+            .set(&name, "alice")
+            .set(&nickname, None::<String>)
+            .set(&title, Some("dr"));
+        let (expression, names) = rendered(update);
+        assert_eq!(expression, "SET #n0 = :v0, #n2 = :v1 REMOVE #n1");
+        assert_eq!(
+            names,
+            [
+                ("#n0".to_owned(), "name".to_owned()),
+                ("#n1".to_owned(), "nickname".to_owned()),
+                ("#n2".to_owned(), "title".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn set_empty_set_removes() {
+        let labels = Attr::<SS>::new("labels");
+        let update = update().set(&labels, HashSet::<String>::new());
+        assert_eq!(rendered(update).0, "REMOVE #n0");
+    }
+
+    const NULLS: Policy = Policy::DEFAULT
+        .nones(Nones::Null)
+        .empty_sets(EmptySets::Null);
+
+    #[derive(crate::Item, crate::Table)]
+    #[amo(policy = NULLS)]
+    struct Note {
+        #[amo(primary, kind = hash)]
+        id: String,
+        title: Option<String>,
+        labels: HashSet<String>,
+    }
+
+    #[test]
+    fn set_uses_item_policy() {
+        let update = NoteTable::new(client(), "notes")
+            .update("a")
+            .unwrap()
+            .set(&Attr::<Optional<S>>::new("title"), None::<String>)
+            .set(&Attr::<SS>::new("labels"), HashSet::<String>::new());
+        let mut placeholders = expr::Placeholders::default();
+        let expression = render(update.actions, &mut placeholders).unwrap();
+        assert_eq!(expression, "SET #n0 = :v0, #n1 = :v1");
+        let (_, values) = placeholders.into_maps();
+        assert!(values
+            .unwrap()
+            .values()
+            .all(|value| value == &AttributeValue::Null(true)));
+    }
+
+    #[test]
+    fn clauses() {
+        let update = update()
+            .increment(&Attr::new("count"), 1_u8)
+            .set_if_not_exists(&Attr::new("created"), 5_u8)
+            .list_append(&Attr::new("log"), vec!["x"])
+            .delete_from_set(
+                &Attr::new("tags"),
+                ["old"].into_iter().collect::<HashSet<_>>(),
+            )
+            .remove(&Attr::<S>::new("gone"));
+        assert_eq!(
+            rendered(update).0,
+            "SET #n1 = if_not_exists(#n1, :v1), #n2 = list_append(if_not_exists(#n2, :v2), :v3) \
+             REMOVE #n4 ADD #n0 :v0 DELETE #n3 :v4"
+        );
+    }
+
+    #[test]
+    fn nested_paths() {
+        let address = Attr::<M>::new("address");
+        let lines = address.field::<L>("lines");
+        let update = update()
+            .set(&address.field::<S>("city"), "Paris")
+            .list_append(&lines, vec!["x"])
+            .remove(&lines.index::<S>(0));
+        let (expression, names) = rendered(update);
+        assert_eq!(
+            expression,
+            "SET #n0.#n1 = :v0, #n0.#n2 = list_append(if_not_exists(#n0.#n2, :v1), :v2) \
+             REMOVE #n0.#n2[0]"
+        );
+        assert_eq!(
+            names,
+            [
+                ("#n0".to_owned(), "address".to_owned()),
+                ("#n1".to_owned(), "city".to_owned()),
+                ("#n2".to_owned(), "lines".to_owned())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_reports_the_first_invalid_value() {
+        let error = update()
+            .set(&Attr::<S>::new("name"), "alice")
+            .increment(&Attr::new("score"), f64::NAN)
+            .increment(&Attr::new("level"), f64::INFINITY)
+            .send()
+            .await
+            .unwrap_err();
+        let WriteError::Serialize(error) = error else {
+            panic!("expected a serialize error, got {error:?}");
+        };
+        assert!(matches!(error.kind(), SerializeErrorKind::InvalidNumber(_)));
+        assert_eq!(error.path().to_string(), "score");
+
+        let error = update()
+            .increment(&Attr::<M>::new("stats").field("score"), f64::NAN)
+            .send()
+            .await
+            .unwrap_err();
+        let WriteError::Serialize(error) = error else {
+            panic!("expected a serialize error, got {error:?}");
+        };
+        assert_eq!(error.path().to_string(), "stats.score");
+    }
+}
@@ -1,15 +1,63 @@
-use aws_sdk_dynamodb::operation::RequestId;
-use aws_sdk_dynamodb::{
-    operation::get_item::builders::GetItemFluentBuilder, types::ConsumedCapacity, Client,
-};
+//! Tables and their secondary indexes, usually declared with
+//! [`#[derive(Table)]`](crate::Table).
+//!
+//! A key's type comes from its field's [`Value`](crate::value::Value) impl, so key fields can't
+//! be serialized another way with `with`:
+//!
+//! ```compile_fail
+//! # mod upper {
+//! #     use amo::error::{DeserializeError, SerializeError};
+//! #     use aws_sdk_dynamodb::types::AttributeValue;
+//! #     pub fn serialize(_: &String) -> Result<AttributeValue, SerializeError> { todo!() }
+//! #     pub fn deserialize(_: AttributeValue) -> Result<String, DeserializeError> { todo!() }
+//! # }
+//! #[derive(amo::Item, amo::Table)]
+//! struct User {
+//!     #[amo(primary, kind = hash, with = upper)]
+//!     id: String,
+//! }
+//! ```
+//!
+//! A local index shares its table's hash key, so only its range key is marked:
+//!
+//! ```compile_fail
+//! #[derive(amo::Item, amo::Table)]
+//! struct Tag {
+//!     #[amo(primary, kind = hash)]
+//!     #[amo(secondary, index = "by-created", kind = hash, local)]
+//!     resource: String,
+//!     #[amo(primary, kind = range)]
+//!     key: String,
+//!     #[amo(secondary, index = "by-created", kind = range, local)]
+//!     created: u64,
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::hash::Hash;
 
-use crate::operation::GetItem;
+use aws_sdk_dynamodb::{types::AttributeValue, Client};
+
+use crate::error::{attribute_kind, DeserializeError, SerializeError, SerializeErrorKind};
+use crate::expr::Attr;
+use crate::operation::{BatchGet, DeleteItem, GetItem, PutItem, Query, Scan, UpdateItem};
 use crate::{
-    error::{ReadError, SerializeError},
     item,
-    value::{self, KeyType, Value},
+    value::{self, KeyType},
+};
+
+mod index;
+
+pub use index::{
+    Global, HashIndex, HashRangeIndex, Index, IndexHandle, IndexKind, IndexProjection, Local,
 };
 
+/// The longest partition (hash) key DynamoDB allows, in bytes.
+pub const MAX_HASH_KEY_SIZE: usize = 2048;
+
+/// The longest sort (range) key DynamoDB allows, in bytes.
+pub const MAX_RANGE_KEY_SIZE: usize = 1024;
+
 pub trait Table: Send + Sync + Sized + Clone {
     type Item;
 
@@ -17,40 +65,256 @@ pub trait Table: Send + Sync + Sized + Clone {
 
     fn client(&self) -> Client;
 
+    /// Scans every item in the table.
     fn all(&self) -> Scan<Self>
     where
         Self::Item: item::Deserialize,
     {
-        todo!()
+        Scan::new(self.client().scan().table_name(self.name()))
     }
 
-    fn put(&self, item: Self::Item) -> PutItem<Self>
+    /// Writes `item`, replacing any item with the same key. See [`put_raw`](Self::put_raw) for
+    /// when it fails.
+    fn put(&self, item: Self::Item) -> Result<PutItem<Self>, SerializeError>
     where
         Self::Item: item::Serialize,
     {
         self.put_raw(item)
     }
 
-    fn put_raw(&self, item: impl item::Serialize) -> PutItem<Self> {
-        todo!()
+    /// Writes any serializable item, replacing any item with the same key. Fails without sending
+    /// anything if the item can't be serialized or is larger than DynamoDB allows.
+    fn put_raw(&self, item: impl item::Serialize) -> Result<PutItem<Self>, SerializeError> {
+        let item = item.serialize_owned_to_map()?;
+        Ok(PutItem::new(
+            self.client()
+                .put_item()
+                .table_name(self.name())
+                .set_item(Some(item)),
+        ))
+    }
+
+    /// Writes `item` whether or not an item with the same key exists. The same as
+    /// [`put`](Self::put), for symmetry with `insert` and `replace`.
+    fn overwrite(&self, item: Self::Item) -> Result<PutItem<Self>, SerializeError>
+    where
+        Self::Item: item::Serialize,
+    {
+        self.put(item)
+    }
+
+    /// A handle for reading the secondary index `X`.
+    fn index<X: Index<Table = Self>>(&self) -> IndexHandle<X> {
+        IndexHandle::new(self.clone())
+    }
+}
+
+/// A table or index, which scans and queries read from.
+pub trait Source: Send + Sync + Sized {
+    /// The type items are read as by default.
+    type Item;
+}
+
+/// The hash key of a table or index that queries read by.
+pub trait KeySchema: Source {
+    type HashKeyType: KeyType;
+    const HASH_KEY_ATTRIBUTE: &'static str;
+}
+
+/// The range key of a table or index that queries can be narrowed by.
+pub trait RangeKeySchema: KeySchema {
+    type RangeKeyType: KeyType;
+    const RANGE_KEY_ATTRIBUTE: &'static str;
+}
+
+/// A table or index that allows strongly consistent reads: every table, and [`Local`] indexes.
+pub trait StrongReads: Source {}
+
+impl<T: Table> Source for T {
+    type Item = T::Item;
+}
+
+impl<T: Table> StrongReads for T {}
+
+impl<T: HashRangeTable> KeySchema for T {
+    type HashKeyType = T::HashKeyType;
+    const HASH_KEY_ATTRIBUTE: &'static str = T::HASH_KEY_ATTRIBUTE;
+}
+
+impl<T: HashRangeTable> RangeKeySchema for T {
+    type RangeKeyType = T::RangeKeyType;
+    const RANGE_KEY_ATTRIBUTE: &'static str = T::RANGE_KEY_ATTRIBUTE;
+}
+
+/// Finds the primary key of a table's items. Implemented for every [`HashTable`] and
+/// [`HashRangeTable`]; `M` is [`HashOnly`] or [`HashAndRange`], and is always inferred.
+pub trait PrimaryKey<M>: Table {
+    /// Picks the key attributes out of a serialized item, checking them against DynamoDB's key
+    /// rules.
+    fn key_of(
+        item: &HashMap<String, AttributeValue>,
+    ) -> Result<HashMap<String, AttributeValue>, SerializeError>;
+}
+
+/// A primary key of the table `T`: a hash key for a [`HashTable`], or a `(hash, range)` pair for
+/// a [`HashRangeTable`]. `M` is inferred, as for [`PrimaryKey`].
+pub trait Key<T, M> {
+    fn serialize_key(self) -> Result<HashMap<String, AttributeValue>, SerializeError>;
+}
+
+/// Marks the key of a [`HashTable`]. See [`PrimaryKey`].
+pub struct HashOnly(());
+
+/// Marks the key of a [`HashRangeTable`]. See [`PrimaryKey`].
+pub struct HashAndRange(());
+
+impl<T: HashTable> PrimaryKey<HashOnly> for T {
+    fn key_of(
+        item: &HashMap<String, AttributeValue>,
+    ) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+        let hash = key_attribute(item, T::HASH_KEY_ATTRIBUTE, MAX_HASH_KEY_SIZE)?;
+        Ok(HashMap::from([(T::HASH_KEY_ATTRIBUTE.to_owned(), hash)]))
+    }
+}
+
+impl<T: HashRangeTable> PrimaryKey<HashAndRange> for T {
+    fn key_of(
+        item: &HashMap<String, AttributeValue>,
+    ) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+        let hash = key_attribute(item, T::HASH_KEY_ATTRIBUTE, MAX_HASH_KEY_SIZE)?;
+        let range = key_attribute(item, T::RANGE_KEY_ATTRIBUTE, MAX_RANGE_KEY_SIZE)?;
+        Ok(HashMap::from([
+            (T::HASH_KEY_ATTRIBUTE.to_owned(), hash),
+            (T::RANGE_KEY_ATTRIBUTE.to_owned(), range),
+        ]))
+    }
+}
+
+impl<T: HashTable, K: value::Serialize<Type = T::HashKeyType>> Key<T, HashOnly> for K {
+    fn serialize_key(self) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+        let hash = serialize_key(self, T::HASH_KEY_ATTRIBUTE, MAX_HASH_KEY_SIZE)?;
+        Ok(HashMap::from([(T::HASH_KEY_ATTRIBUTE.to_owned(), hash)]))
+    }
+}
+
+impl<T, H, R> Key<T, HashAndRange> for (H, R)
+where
+    T: HashRangeTable,
+    H: value::Serialize<Type = T::HashKeyType>,
+    R: value::Serialize<Type = T::RangeKeyType>,
+{
+    fn serialize_key(self) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+        let (hash, range) = self;
+        let hash = serialize_key(hash, T::HASH_KEY_ATTRIBUTE, MAX_HASH_KEY_SIZE)?;
+        let range = serialize_key(range, T::RANGE_KEY_ATTRIBUTE, MAX_RANGE_KEY_SIZE)?;
+        Ok(HashMap::from([
+            (T::HASH_KEY_ATTRIBUTE.to_owned(), hash),
+            (T::RANGE_KEY_ATTRIBUTE.to_owned(), range),
+        ]))
     }
 }
 
+/// A table whose primary key is just a hash key.
+///
+/// Reading, updating or deleting an item fails without sending anything, with a
+/// [`SerializeError`] naming the key attribute, if the key is empty or longer than DynamoDB
+/// allows.
 pub trait HashTable: Table {
     type HashKeyType: KeyType;
     const HASH_KEY_ATTRIBUTE: &'static str;
 
-    fn get_raw(&self, hash: impl value::Serialize<Type = Self::HashKeyType>) -> GetItem<Self>
+    fn get_raw(
+        &self,
+        hash: impl value::Serialize<Type = Self::HashKeyType>,
+    ) -> Result<GetItem<Self>, SerializeError>
     where
         Self::Item: item::Deserialize,
     {
-        let request = hash
-            .serialize_owned()
-            .map(|h| self.client().get_item().key(Self::HASH_KEY_ATTRIBUTE, h));
-        GetItem::new(request)
+        let hash = serialize_key(hash, Self::HASH_KEY_ATTRIBUTE, MAX_HASH_KEY_SIZE)?;
+        Ok(GetItem::new(
+            self.client()
+                .get_item()
+                .table_name(self.name())
+                .key(Self::HASH_KEY_ATTRIBUTE, hash),
+        ))
+    }
+
+    /// Updates the item with the given key. See [`UpdateItem`] for the available actions.
+    fn update_raw(
+        &self,
+        hash: impl value::Serialize<Type = Self::HashKeyType>,
+    ) -> Result<UpdateItem<Self>, SerializeError> {
+        let hash = serialize_key(hash, Self::HASH_KEY_ATTRIBUTE, MAX_HASH_KEY_SIZE)?;
+        Ok(UpdateItem::new(
+            self.client()
+                .update_item()
+                .table_name(self.name())
+                .key(Self::HASH_KEY_ATTRIBUTE, hash),
+        ))
+    }
+
+    /// Deletes the item with the given key.
+    fn delete_raw(
+        &self,
+        hash: impl value::Serialize<Type = Self::HashKeyType>,
+    ) -> Result<DeleteItem<Self>, SerializeError> {
+        let hash = serialize_key(hash, Self::HASH_KEY_ATTRIBUTE, MAX_HASH_KEY_SIZE)?;
+        Ok(DeleteItem::new(
+            self.client()
+                .delete_item()
+                .table_name(self.name())
+                .key(Self::HASH_KEY_ATTRIBUTE, hash),
+        ))
+    }
+
+    /// Reads the items with the given keys, in batches. See [`BatchGet`].
+    fn batch_get<K>(&self, keys: impl IntoIterator<Item = K>) -> BatchGet<Self, K>
+    where
+        K: value::Serialize<Type = Self::HashKeyType> + value::Deserialize + Hash + Eq + Clone,
+        Self::Item: item::Deserialize,
+    {
+        BatchGet::new(
+            self,
+            keys,
+            vec![Self::HASH_KEY_ATTRIBUTE],
+            |hash| {
+                let hash = serialize_key(hash, Self::HASH_KEY_ATTRIBUTE, MAX_HASH_KEY_SIZE)?;
+                Ok(HashMap::from([(Self::HASH_KEY_ATTRIBUTE.to_owned(), hash)]))
+            },
+            |key| deserialize_key(key, Self::HASH_KEY_ATTRIBUTE),
+        )
+    }
+
+    /// Writes `item`, failing with
+    /// [`WriteError::ConditionalCheckFailed`](crate::error::WriteError::ConditionalCheckFailed)
+    /// if an item with the same key exists.
+    fn insert(&self, item: Self::Item) -> Result<PutItem<Self>, SerializeError>
+    where
+        Self::Item: item::Serialize,
+    {
+        Ok(self
+            .put(item)?
+            .condition(Attr::<Self::HashKeyType>::new(Self::HASH_KEY_ATTRIBUTE).not_exists()))
+    }
+
+    /// Writes `item`, failing with
+    /// [`WriteError::ConditionalCheckFailed`](crate::error::WriteError::ConditionalCheckFailed)
+    /// unless an item with the same key exists.
+    fn replace(&self, item: Self::Item) -> Result<PutItem<Self>, SerializeError>
+    where
+        Self::Item: item::Serialize,
+    {
+        Ok(self
+            .put(item)?
+            .condition(Attr::<Self::HashKeyType>::new(Self::HASH_KEY_ATTRIBUTE).exists()))
     }
 }
 
+/// A table whose primary key is a hash key and a range key.
+///
+/// As with a [`HashTable`], reading, updating or deleting an item fails straight away if either
+/// key component is empty or too long. A query only checks its hash key when it's sent; see
+/// [`Query`].
 pub trait HashRangeTable: Table {
     type HashKeyType: KeyType;
     const HASH_KEY_ATTRIBUTE: &'static str;
@@ -62,21 +326,247 @@ pub trait HashRangeTable: Table {
         &self,
         hash: impl value::Serialize<Type = Self::HashKeyType>,
         range: impl value::Serialize<Type = Self::RangeKeyType>,
-    ) -> GetItem<Self>
+    ) -> Result<GetItem<Self>, SerializeError>
+    where
+        Self::Item: item::Deserialize,
+    {
+        let (hash, range) = serialize_hash_range::<Self>(hash, range)?;
+        Ok(GetItem::new(
+            self.client()
+                .get_item()
+                .table_name(self.name())
+                .key(Self::HASH_KEY_ATTRIBUTE, hash)
+                .key(Self::RANGE_KEY_ATTRIBUTE, range),
+        ))
+    }
+
+    /// Updates the item with the given key. See [`UpdateItem`] for the available actions.
+    fn update_raw(
+        &self,
+        hash: impl value::Serialize<Type = Self::HashKeyType>,
+        range: impl value::Serialize<Type = Self::RangeKeyType>,
+    ) -> Result<UpdateItem<Self>, SerializeError> {
+        let (hash, range) = serialize_hash_range::<Self>(hash, range)?;
+        Ok(UpdateItem::new(
+            self.client()
+                .update_item()
+                .table_name(self.name())
+                .key(Self::HASH_KEY_ATTRIBUTE, hash)
+                .key(Self::RANGE_KEY_ATTRIBUTE, range),
+        ))
+    }
+
+    /// Deletes the item with the given key.
+    fn delete_raw(
+        &self,
+        hash: impl value::Serialize<Type = Self::HashKeyType>,
+        range: impl value::Serialize<Type = Self::RangeKeyType>,
+    ) -> Result<DeleteItem<Self>, SerializeError> {
+        let (hash, range) = serialize_hash_range::<Self>(hash, range)?;
+        Ok(DeleteItem::new(
+            self.client()
+                .delete_item()
+                .table_name(self.name())
+                .key(Self::HASH_KEY_ATTRIBUTE, hash)
+                .key(Self::RANGE_KEY_ATTRIBUTE, range),
+        ))
+    }
+
+    /// Reads the items with the given `(hash, range)` keys, in batches. See [`BatchGet`].
+    fn batch_get<H, R>(&self, keys: impl IntoIterator<Item = (H, R)>) -> BatchGet<Self, (H, R)>
     where
+        H: value::Serialize<Type = Self::HashKeyType> + value::Deserialize + Hash + Eq + Clone,
+        R: value::Serialize<Type = Self::RangeKeyType> + value::Deserialize + Hash + Eq + Clone,
         Self::Item: item::Deserialize,
     {
-        let request = hash.serialize_owned().and_then(|h| {
-            range.serialize_owned().map(|r| {
-                self.client()
-                    .get_item()
-                    .key(Self::HASH_KEY_ATTRIBUTE, h)
-                    .key(Self::RANGE_KEY_ATTRIBUTE, r)
-            })
-        });
-        GetItem::new(request)
+        BatchGet::new(
+            self,
+            keys,
+            vec![Self::HASH_KEY_ATTRIBUTE, Self::RANGE_KEY_ATTRIBUTE],
+            |(hash, range)| {
+                let (hash, range) = serialize_hash_range::<Self>(hash, range)?;
+                Ok(HashMap::from([
+                    (Self::HASH_KEY_ATTRIBUTE.to_owned(), hash),
+                    (Self::RANGE_KEY_ATTRIBUTE.to_owned(), range),
+                ]))
+            },
+            |key| {
+                Ok((
+                    deserialize_key(key, Self::HASH_KEY_ATTRIBUTE)?,
+                    deserialize_key(key, Self::RANGE_KEY_ATTRIBUTE)?,
+                ))
+            },
+        )
     }
+
+    /// Reads the items with the given hash key. See [`Query`] for narrowing it by range key.
+    fn query_raw(&self, hash: impl value::Serialize<Type = Self::HashKeyType>) -> Query<Self>
+    where
+        Self::Item: item::Deserialize,
+    {
+        let hash = serialize_key(hash, Self::HASH_KEY_ATTRIBUTE, MAX_HASH_KEY_SIZE);
+        Query::new(self.client().query().table_name(self.name()), hash)
+    }
+
+    /// Writes `item`, failing with
+    /// [`WriteError::ConditionalCheckFailed`](crate::error::WriteError::ConditionalCheckFailed)
+    /// if an item with the same key exists.
+    fn insert(&self, item: Self::Item) -> Result<PutItem<Self>, SerializeError>
+    where
+        Self::Item: item::Serialize,
+    {
+        Ok(self
+            .put(item)?
+            .condition(Attr::<Self::HashKeyType>::new(Self::HASH_KEY_ATTRIBUTE).not_exists()))
+    }
+
+    /// Writes `item`, failing with
+    /// [`WriteError::ConditionalCheckFailed`](crate::error::WriteError::ConditionalCheckFailed)
+    /// unless an item with the same key exists.
+    fn replace(&self, item: Self::Item) -> Result<PutItem<Self>, SerializeError>
+    where
+        Self::Item: item::Serialize,
+    {
+        Ok(self
+            .put(item)?
+            .condition(Attr::<Self::HashKeyType>::new(Self::HASH_KEY_ATTRIBUTE).exists()))
+    }
+}
+
+/// Serializes one component of a primary key, checking it against DynamoDB's key rules. Errors name
+/// the key attribute.
+pub(crate) fn serialize_key<K: KeyType>(
+    value: impl value::Serialize<Type = K>,
+    attribute: &str,
+    limit: usize,
+) -> Result<AttributeValue, SerializeError> {
+    value
+        .serialize_owned()
+        .and_then(|value| check_key(value, limit))
+        .map_err(|e| e.at_field(attribute))
+}
+
+/// Serializes both components of a [`HashRangeTable`]'s primary key.
+fn serialize_hash_range<T: HashRangeTable>(
+    hash: impl value::Serialize<Type = T::HashKeyType>,
+    range: impl value::Serialize<Type = T::RangeKeyType>,
+) -> Result<(AttributeValue, AttributeValue), SerializeError> {
+    Ok((
+        serialize_key(hash, T::HASH_KEY_ATTRIBUTE, MAX_HASH_KEY_SIZE)?,
+        serialize_key(range, T::RANGE_KEY_ATTRIBUTE, MAX_RANGE_KEY_SIZE)?,
+    ))
 }
 
-pub struct Scan<T>(T);
-pub struct PutItem<T>(T);
+/// Checks that a serialized key is a string, number or binary, and that a string or binary key is
+/// neither empty nor longer than `limit` bytes.
+pub(crate) fn check_key(
+    value: AttributeValue,
+    limit: usize,
+) -> Result<AttributeValue, SerializeError> {
+    let size = match &value {
+        AttributeValue::S(s) => s.len(),
+        AttributeValue::B(b) => b.as_ref().len(),
+        AttributeValue::N(_) => return Ok(value),
+        value => {
+            return Err(SerializeError::invalid(format_args!(
+                "key attributes must be S, N or B, not {}",
+                attribute_kind(value)
+            )))
+        }
+    };
+    if size == 0 {
+        Err(SerializeError::new(SerializeErrorKind::EmptyKey))
+    } else if size > limit {
+        Err(SerializeError::new(SerializeErrorKind::KeyTooLong {
+            size,
+            limit,
+        }))
+    } else {
+        Ok(value)
+    }
+}
+
+/// Reads one component of a primary key from an item or key.
+pub(crate) fn deserialize_key<V: value::Deserialize>(
+    item: &HashMap<String, AttributeValue>,
+    attribute: &str,
+) -> Result<V, DeserializeError> {
+    let value = item
+        .get(attribute)
+        .ok_or_else(|| DeserializeError::missing_attribute(attribute))?;
+    V::deserialize_owned(value.clone()).map_err(|e| e.at_field(attribute))
+}
+
+/// Copies one key attribute out of a serialized item, checking it against DynamoDB's key rules.
+fn key_attribute(
+    item: &HashMap<String, AttributeValue>,
+    attribute: &str,
+    limit: usize,
+) -> Result<AttributeValue, SerializeError> {
+    let value = item
+        .get(attribute)
+        .ok_or_else(|| SerializeError::invalid("items must have every key attribute"))
+        .map_err(|e| e.at_field(attribute))?;
+    check_key(value.clone(), limit).map_err(|e| e.at_field(attribute))
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodb::primitives::Blob;
+
+    use super::*;
+
+    #[test]
+    fn check_key_types() {
+        assert!(check_key(AttributeValue::S("a".into()), 10).is_ok());
+        assert!(check_key(AttributeValue::N("1".into()), 10).is_ok());
+        assert!(check_key(AttributeValue::B(Blob::new(vec![1])), 10).is_ok());
+        for value in [
+            AttributeValue::Null(true),
+            AttributeValue::Bool(true),
+            AttributeValue::Ss(vec!["a".into()]),
+            AttributeValue::L(Vec::new()),
+        ] {
+            let error = check_key(value, 10).unwrap_err();
+            assert!(
+                matches!(error.kind(), SerializeErrorKind::Invalid(_)),
+                "{error}"
+            );
+        }
+    }
+
+    #[test]
+    fn check_key_size() {
+        assert_eq!(
+            check_key(AttributeValue::S(String::new()), 10)
+                .unwrap_err()
+                .kind(),
+            &SerializeErrorKind::EmptyKey
+        );
+        assert_eq!(
+            check_key(AttributeValue::B(Blob::new(Vec::new())), 10)
+                .unwrap_err()
+                .kind(),
+            &SerializeErrorKind::EmptyKey
+        );
+        assert!(check_key(AttributeValue::S("a".repeat(10)), 10).is_ok());
+        assert_eq!(
+            check_key(AttributeValue::S("a".repeat(11)), 10)
+                .unwrap_err()
+                .kind(),
+            &SerializeErrorKind::KeyTooLong {
+                size: 11,
+                limit: 10
+            }
+        );
+    }
+
+    #[test]
+    fn serialize_key_names_attribute() {
+        let error = serialize_key(AttributeValue::Null(true), "id", 10).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "at id: key attributes must be S, N or B, not NULL"
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use std::marker::PhantomData;
+
+use crate::{
+    error::SerializeError,
+    item,
+    operation::{Query, Scan},
+    value::{self, KeyType, Type},
+};
+
+use super::{
+    serialize_key, HashAndRange, HashOnly, HashRangeTable, HashTable, KeySchema, RangeKeySchema,
+    Source, StrongReads, Table, MAX_HASH_KEY_SIZE,
+};
+
+/// A secondary index of a table, implemented by a marker type along with [`HashIndex`], and
+/// [`HashRangeIndex`] if the index has a range key. Read it through [`Table::index`].
+///
+/// Whether strongly consistent reads are allowed depends on [`Kind`](Self::Kind). A [`Local`]
+/// index supports them, and a [`Global`] one doesn't, so asking for them doesn't compile:
+///
+/// ```compile_fail
+/// use amo::operation::Consistency;
+/// use amo::table::{Global, HashIndex, HashRangeIndex, Index, IndexProjection, Table};
+///
+/// # #[derive(Clone, amo::Item, amo::Table)]
+/// # struct Tag {
+/// #     #[amo(primary, kind = hash)]
+/// #     resource: String,
+/// #     #[amo(primary, kind = range)]
+/// #     key: String,
+/// #     account: String,
+/// # }
+/// struct ByAccount;
+///
+/// impl Index for ByAccount {
+///     type Table = TagTable;
+///     type Item = Tag;
+///     type Kind = Global;
+///     const NAME: &'static str = "by-account";
+///     const PROJECTION: IndexProjection = IndexProjection::All;
+/// }
+///
+/// impl HashIndex for ByAccount {
+///     type HashKeyType = amo::value::S;
+///     const HASH_KEY_ATTRIBUTE: &'static str = "account";
+/// }
+///
+/// impl HashRangeIndex for ByAccount {
+///     type RangeKeyType = amo::value::S;
+///     const RANGE_KEY_ATTRIBUTE: &'static str = "key";
+/// }
+///
+/// # fn f(table: TagTable) {
+/// table.index::<ByAccount>().query("123").consistency(Consistency::Strong);
+/// # }
+/// ```
+pub trait Index: 'static {
+    type Table: Table;
+
+    /// The type items are read as by default. Unless the index projects every attribute, this is
+    /// usually a projection type with just the projected attributes.
+    type Item;
+
+    /// [`Global`] or [`Local`].
+    type Kind: IndexKind;
+
+    /// The index's name.
+    const NAME: &'static str;
+
+    /// The attributes the index holds. Unless it holds every attribute, reads only fetch the
+    /// attributes their item type reads, and fail before anything is sent if the item type reads
+    /// one the index doesn't hold.
+    const PROJECTION: IndexProjection;
+}
+
+/// The attributes a secondary index holds, as set when the index was created. See the
+/// [DynamoDB documentation](https://docs.aws.amazon.com/amazondynamodb/latest/developerguide/GSI.html#GSI.Projections).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndexProjection {
+    /// Every attribute (`ALL`).
+    All,
+    /// Only the key attributes of the table and the index (`KEYS_ONLY`).
+    KeysOnly { keys: &'static [&'static str] },
+    /// The key attributes and the listed non-key `attributes` (`INCLUDE`).
+    Include {
+        keys: &'static [&'static str],
+        attributes: &'static [&'static str],
+    },
+}
+
+impl IndexProjection {
+    /// Whether the index holds `attribute`.
+    pub fn contains(&self, attribute: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::KeysOnly { keys } => keys.contains(&attribute),
+            Self::Include { keys, attributes } => {
+                keys.contains(&attribute) || attributes.contains(&attribute)
+            }
+        }
+    }
+}
+
+/// The hash key of an index. A [`Local`] index's must be its table's, which is checked when a
+/// query is built.
+pub trait HashIndex: Index {
+    type HashKeyType: KeyType;
+    const HASH_KEY_ATTRIBUTE: &'static str;
+}
+
+/// The range key of an index that has one. [`Local`] indexes always do.
+pub trait HashRangeIndex: HashIndex {
+    type RangeKeyType: KeyType;
+    const RANGE_KEY_ATTRIBUTE: &'static str;
+}
+
+/// The kind of a secondary index, [`Global`] or [`Local`].
+pub trait IndexKind: private::SealedIndexKind {}
+
+mod private {
+    use crate::value::KeyType;
+
+    #[doc(hidden)]
+    pub trait SealedIndexKind {
+        const LOCAL: bool;
+    }
+
+    /// The hash key of a table, for checking a local index's against it. `M` is inferred, as for
+    /// [`PrimaryKey`](super::super::PrimaryKey).
+    #[doc(hidden)]
+    pub trait TableHashKey<M> {
+        type Type: KeyType;
+        const ATTRIBUTE: &'static str;
+    }
+}
+
+impl<T: HashTable> private::TableHashKey<HashOnly> for T {
+    type Type = T::HashKeyType;
+    const ATTRIBUTE: &'static str = T::HASH_KEY_ATTRIBUTE;
+}
+
+impl<T: HashRangeTable> private::TableHashKey<HashAndRange> for T {
+    type Type = T::HashKeyType;
+    const ATTRIBUTE: &'static str = T::HASH_KEY_ATTRIBUTE;
+}
+
+/// A global secondary index, whose hash key may differ from the table's. Reads are always
+/// eventually consistent.
+pub struct Global(());
+
+impl IndexKind for Global {}
+impl private::SealedIndexKind for Global {
+    const LOCAL: bool = false;
+}
+
+/// A local secondary index, which shares the table's hash key. Reads may be strongly consistent.
+pub struct Local(());
+
+impl IndexKind for Local {}
+impl private::SealedIndexKind for Local {
+    const LOCAL: bool = true;
+}
+
+/// A handle to the index `X` of a table. Returned by [`Table::index`].
+pub struct IndexHandle<X: Index> {
+    table: X::Table,
+    _index: PhantomData<fn() -> X>,
+}
+
+impl<X: Index> IndexHandle<X> {
+    pub(crate) fn new(table: X::Table) -> Self {
+        Self {
+            table,
+            _index: PhantomData,
+        }
+    }
+
+    /// The table the index belongs to.
+    pub fn table(&self) -> &X::Table {
+        &self.table
+    }
+
+    /// Scans every item in the index.
+    pub fn all(&self) -> Scan<Self>
+    where
+        X::Item: item::Deserialize,
+    {
+        Scan::new(
+            self.table
+                .client()
+                .scan()
+                .table_name(self.table.name())
+                .index_name(X::NAME),
+        )
+        .in_index(X::NAME, X::PROJECTION)
+    }
+}
+
+impl<X: HashIndex> IndexHandle<X> {
+    /// Reads the items with the given index hash key. See [`Query`] for narrowing it by range key.
+    /// `M` is inferred, as for [`PrimaryKey`](super::PrimaryKey).
+    pub fn query<M>(&self, hash: impl value::Serialize<Type = X::HashKeyType>) -> Query<Self>
+    where
+        X::Item: item::Deserialize,
+        X::Table: private::TableHashKey<M>,
+    {
+        let hash = check_hash_key::<X, M>()
+            .and_then(|()| serialize_key(hash, X::HASH_KEY_ATTRIBUTE, MAX_HASH_KEY_SIZE));
+        let request = self
+            .table
+            .client()
+            .query()
+            .table_name(self.table.name())
+            .index_name(X::NAME);
+        Query::new(request, hash).in_index(X::NAME, X::PROJECTION)
+    }
+}
+
+/// Checks that a [`Local`] index has its table's hash key.
+fn check_hash_key<X: HashIndex, M>() -> Result<(), SerializeError>
+where
+    X::Table: private::TableHashKey<M>,
+{
+    let table_hash = <X::Table as private::TableHashKey<M>>::ATTRIBUTE;
+    let table_type = <<X::Table as private::TableHashKey<M>>::Type as Type>::NAME;
+    let local = <X::Kind as private::SealedIndexKind>::LOCAL;
+    if local && (X::HASH_KEY_ATTRIBUTE != table_hash || X::HashKeyType::NAME != table_type) {
+        return Err(SerializeError::invalid(format!(
+            "local index `{}` must have the table's hash key, `{table_hash}` of type {table_type}",
+            X::NAME
+        )));
+    }
+    Ok(())
+}
+
+// Not derived, which would require `X: Clone`.
+impl<X: Index> Clone for IndexHandle<X> {
+    fn clone(&self) -> Self {
+        Self::new(self.table.clone())
+    }
+}
+
+impl<X: Index> Source for IndexHandle<X> {
+    type Item = X::Item;
+}
+
+impl<X: Index<Kind = Local>> StrongReads for IndexHandle<X> {}
+
+impl<X: HashIndex> KeySchema for IndexHandle<X> {
+    type HashKeyType = X::HashKeyType;
+    const HASH_KEY_ATTRIBUTE: &'static str = X::HASH_KEY_ATTRIBUTE;
+}
+
+impl<X: HashRangeIndex> RangeKeySchema for IndexHandle<X> {
+    type RangeKeyType = X::RangeKeyType;
+    const RANGE_KEY_ATTRIBUTE: &'static str = X::RANGE_KEY_ATTRIBUTE;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::SerializeErrorKind;
+    use crate::test_support::{TagByAccount, TagByCreated, TagTable};
+    use crate::value::{N, S};
+
+    use super::*;
+
+    /// Local indexes whose hash key isn't the table's, which the derive never generates.
+    macro_rules! local_index {
+        ($name:ident, $hash_type:ty, $hash:literal) => {
+            struct $name;
+
+            impl Index for $name {
+                type Table = TagTable;
+                type Item = ();
+                type Kind = Local;
+                const NAME: &'static str = stringify!($name);
+                const PROJECTION: IndexProjection = IndexProjection::All;
+            }
+
+            impl HashIndex for $name {
+                type HashKeyType = $hash_type;
+                const HASH_KEY_ATTRIBUTE: &'static str = $hash;
+            }
+        };
+    }
+
+    local_index!(OtherAttribute, S, "account");
+    local_index!(OtherType, N, "resource");
+
+    #[test]
+    fn global_hash_key() {
+        assert!(check_hash_key::<TagByAccount, _>().is_ok());
+        assert_eq!(<IndexHandle<TagByAccount>>::HASH_KEY_ATTRIBUTE, "account");
+    }
+
+    #[test]
+    fn local_hash_key() {
+        assert!(check_hash_key::<TagByCreated, _>().is_ok());
+        assert_eq!(<IndexHandle<TagByCreated>>::RANGE_KEY_ATTRIBUTE, "created");
+
+        for error in [
+            check_hash_key::<OtherAttribute, _>().unwrap_err(),
+            check_hash_key::<OtherType, _>().unwrap_err(),
+        ] {
+            assert!(
+                matches!(error.kind(), SerializeErrorKind::Invalid(_)),
+                "{error}"
+            );
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Tables shared by the unit tests, derived the same way users declare theirs.
+
+use aws_sdk_dynamodb::{config::BehaviorVersion, Client, Config};
+
+/// A client with no region or credentials, for tests that build requests without sending them.
+pub(crate) fn client() -> Client {
+    Client::from_conf(
+        Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .build(),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, crate::Item, crate::Table)]
+pub(crate) struct Thing {
+    #[amo(primary, kind = hash)]
+    pub id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, crate::Item, crate::Table)]
+pub(crate) struct Tag {
+    #[amo(primary, kind = hash)]
+    pub resource: String,
+    #[amo(primary, kind = range)]
+    pub key: String,
+    #[amo(secondary, index = "by-account", kind = hash)]
+    pub account: String,
+    #[amo(secondary, index = "by-created", kind = range, local)]
+    pub created: u64,
+}
@@ -1,12 +1,22 @@
-use std::hash::Hash;
+use std::collections::HashMap;
+use std::marker::PhantomData;
 use std::sync::Arc;
-use std::{collections::HashMap, hash::BuildHasher};
 
 use aws_sdk_dynamodb::primitives::Blob;
 use aws_sdk_dynamodb::types::AttributeValue;
 
 use crate::error::{DeserializeError, SerializeError};
 
+mod list;
+mod map;
+mod number;
+mod option;
+mod policy;
+mod set;
+
+pub use number::Number;
+pub use policy::{EmptySets, Nones, Nulls, Policy};
+
 pub trait Type: private::SealedType {
     const NAME: &'static str;
 
@@ -16,10 +26,20 @@ pub trait Type: private::SealedType {
     fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue>;
 }
 
+/// Types that can be used as hash or range keys. DynamoDB only allows strings, numbers and binary
+/// there, so types such as [`BOOL`] and [`NULL`] don't implement this.
 pub trait KeyType: Type + private::SealedKeyType {}
 impl<T: Type + private::SealedKeyType> KeyType for T {}
 
-pub trait Value: Serialize<Type = <Self as Value>::Type> + Deserialize<Type = <Self as Value>::Type> {
+/// The types DynamoDB can order, for comparisons such as `<` and `BETWEEN`: [`S`], [`N`] and [`B`].
+pub trait OrderedType: KeyType {}
+impl OrderedType for S {}
+impl OrderedType for N {}
+impl OrderedType for B {}
+
+pub trait Value:
+    Serialize<Type = <Self as Value>::Type> + Deserialize<Type = <Self as Value>::Type>
+{
     type Type: Type;
 }
 impl<V: Serialize + Deserialize<Type = <V as Serialize>::Type>> Value for V {
@@ -31,7 +51,10 @@ pub trait Serialize {
 
     fn serialize_raw(&self) -> Result<<Self::Type as Type>::Raw, SerializeError>;
 
-    fn serialize_owned_raw(self) -> Result<<Self::Type as Type>::Raw, SerializeError> where Self: Sized{
+    fn serialize_owned_raw(self) -> Result<<Self::Type as Type>::Raw, SerializeError>
+    where
+        Self: Sized,
+    {
         self.serialize_raw()
     }
 
@@ -39,8 +62,31 @@ pub trait Serialize {
         self.serialize_raw().map(Self::Type::to_attribute_value)
     }
 
-    fn serialize_owned(self) -> Result<AttributeValue, SerializeError> where Self: Sized{
-        self.serialize_owned_raw().map(Self::Type::to_attribute_value)
+    fn serialize_owned(self) -> Result<AttributeValue, SerializeError>
+    where
+        Self: Sized,
+    {
+        self.serialize_owned_raw()
+            .map(Self::Type::to_attribute_value)
+    }
+
+    /// Serializes this value as a top-level item attribute, returning `None` if `policy` says the
+    /// attribute should be omitted from the item entirely.
+    fn serialize_attribute(
+        &self,
+        _policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        self.serialize().map(Some)
+    }
+
+    fn serialize_owned_attribute(
+        self,
+        _policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError>
+    where
+        Self: Sized,
+    {
+        self.serialize_owned().map(Some)
     }
 }
 
@@ -52,9 +98,21 @@ pub trait Deserialize: Sized {
     fn deserialize_owned(value: AttributeValue) -> Result<Self, DeserializeError> {
         match Self::Type::from_attribute_value(value) {
             Ok(raw) => Self::deserialize_owned_raw(raw),
-            Err(value) => Err(DeserializeError::unexpected_value_type(Self::Type::NAME, value))
+            Err(value) => Err(DeserializeError::unexpected_value_type(
+                Self::Type::NAME,
+                value,
+            )),
         }
     }
+
+    /// Deserializes a top-level item attribute, where `None` means the attribute was absent.
+    /// Returns `Ok(None)` if the attribute is absent and `policy` doesn't give it a value.
+    fn deserialize_attribute(
+        value: Option<AttributeValue>,
+        _policy: &Policy,
+    ) -> Result<Option<Self>, DeserializeError> {
+        value.map(Self::deserialize_owned).transpose()
+    }
 }
 
 mod private {
@@ -67,21 +125,21 @@ mod private {
 
 pub struct S(());
 
-impl Type for S { 
+impl Type for S {
     const NAME: &'static str = "S";
 
     type Raw = String;
-    
+
     fn to_attribute_value(raw: Self::Raw) -> AttributeValue {
         AttributeValue::S(raw)
     }
-    
+
     fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
         match value {
             AttributeValue::S(s) => Ok(s),
             value => Err(value),
         }
-    } 
+    }
 }
 
 impl private::SealedType for S {}
@@ -89,21 +147,21 @@ impl private::SealedKeyType for S {}
 
 pub struct N(());
 
-impl Type for N { 
+impl Type for N {
     const NAME: &'static str = "N";
 
     type Raw = String;
-    
+
     fn to_attribute_value(raw: Self::Raw) -> AttributeValue {
         AttributeValue::N(raw)
     }
-    
+
     fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
         match value {
             AttributeValue::N(n) => Ok(n),
             value => Err(value),
         }
-    } 
+    }
 }
 
 impl private::SealedType for N {}
@@ -111,46 +169,273 @@ impl private::SealedKeyType for N {}
 
 pub struct B(());
 
-impl Type for B { 
+impl Type for B {
     const NAME: &'static str = "B";
 
     type Raw = Vec<u8>;
-    
+
     fn to_attribute_value(raw: Self::Raw) -> AttributeValue {
         AttributeValue::B(Blob::new(raw))
     }
-    
+
     fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
         match value {
             AttributeValue::B(b) => Ok(b.into_inner()),
             value => Err(value),
         }
-    } 
+    }
 }
 
 impl private::SealedType for B {}
 impl private::SealedKeyType for B {}
 
+/// Booleans. Not a [`KeyType`]: DynamoDB keys can only be strings, numbers or binary.
+pub struct BOOL(());
+
+impl Type for BOOL {
+    const NAME: &'static str = "BOOL";
+
+    type Raw = bool;
+
+    fn to_attribute_value(raw: Self::Raw) -> AttributeValue {
+        AttributeValue::Bool(raw)
+    }
+
+    fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
+        match value {
+            AttributeValue::Bool(b) => Ok(b),
+            value => Err(value),
+        }
+    }
+}
+
+impl private::SealedType for BOOL {}
+
+/// The `NULL` attribute, which carries no value. Not a [`KeyType`].
+pub struct NULL(());
+
+impl Type for NULL {
+    const NAME: &'static str = "NULL";
+
+    type Raw = ();
+
+    fn to_attribute_value(_raw: Self::Raw) -> AttributeValue {
+        AttributeValue::Null(true)
+    }
+
+    fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
+        match value {
+            AttributeValue::Null(_) => Ok(()),
+            value => Err(value),
+        }
+    }
+}
+
+impl private::SealedType for NULL {}
+
+/// A value of type `T`, or `NULL`: the type of `Option`s. Not a [`KeyType`], since keys can't be
+/// `NULL`, so an `Option` can't be passed where a key is expected:
+///
+/// ```compile_fail
+/// use amo::table::HashTable;
+///
+/// #[derive(amo::Item, amo::Table)]
+/// struct User {
+///     #[amo(primary, kind = hash)]
+///     id: String,
+/// }
+///
+/// fn get(users: &UserTable) {
+///     users.get_raw(Some("alice".to_owned()));
+/// }
+/// ```
+pub struct Optional<T>(PhantomData<fn() -> T>);
+
+impl<T: Type> Type for Optional<T> {
+    const NAME: &'static str = T::NAME;
+
+    type Raw = Option<T::Raw>;
+
+    fn to_attribute_value(raw: Self::Raw) -> AttributeValue {
+        match raw {
+            Some(raw) => T::to_attribute_value(raw),
+            None => AttributeValue::Null(true),
+        }
+    }
+
+    fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
+        match value {
+            AttributeValue::Null(_) => Ok(None),
+            value => T::from_attribute_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: Type> private::SealedType for Optional<T> {}
+
+/// String sets.
+pub struct SS(());
+
+impl Type for SS {
+    const NAME: &'static str = "SS";
+
+    type Raw = Vec<String>;
+
+    fn to_attribute_value(raw: Self::Raw) -> AttributeValue {
+        AttributeValue::Ss(raw)
+    }
+
+    fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
+        match value {
+            AttributeValue::Ss(ss) => Ok(ss),
+            value => Err(value),
+        }
+    }
+}
+
+impl private::SealedType for SS {}
+
+/// Number sets.
+pub struct NS(());
+
+impl Type for NS {
+    const NAME: &'static str = "NS";
+
+    type Raw = Vec<String>;
+
+    fn to_attribute_value(raw: Self::Raw) -> AttributeValue {
+        AttributeValue::Ns(raw)
+    }
+
+    fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
+        match value {
+            AttributeValue::Ns(ns) => Ok(ns),
+            value => Err(value),
+        }
+    }
+}
+
+impl private::SealedType for NS {}
+
+/// Binary sets.
+pub struct BS(());
+
+impl Type for BS {
+    const NAME: &'static str = "BS";
+
+    type Raw = Vec<Vec<u8>>;
+
+    fn to_attribute_value(raw: Self::Raw) -> AttributeValue {
+        AttributeValue::Bs(raw.into_iter().map(Blob::new).collect())
+    }
+
+    fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
+        match value {
+            AttributeValue::Bs(bs) => Ok(bs.into_iter().map(Blob::into_inner).collect()),
+            value => Err(value),
+        }
+    }
+}
+
+impl private::SealedType for BS {}
+
+/// Types that DynamoDB can store in a set, and the set type that holds them.
+pub trait SetElementType: Type {
+    type Set: Type<Raw = Vec<Self::Raw>>;
+}
+
+impl SetElementType for S {
+    type Set = SS;
+}
+
+impl SetElementType for N {
+    type Set = NS;
+}
+
+impl SetElementType for B {
+    type Set = BS;
+}
+
+/// The set types, [`SS`], [`NS`] and [`BS`].
+pub trait SetType: Type {
+    type Element: SetElementType<Set = Self>;
+}
+
+impl SetType for SS {
+    type Element = S;
+}
+
+impl SetType for NS {
+    type Element = N;
+}
+
+impl SetType for BS {
+    type Element = B;
+}
+
+/// Lists of arbitrary, possibly mixed, values.
+pub struct L(());
+
+impl Type for L {
+    const NAME: &'static str = "L";
+
+    type Raw = Vec<AttributeValue>;
+
+    fn to_attribute_value(raw: Self::Raw) -> AttributeValue {
+        AttributeValue::L(raw)
+    }
+
+    fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
+        match value {
+            AttributeValue::L(l) => Ok(l),
+            value => Err(value),
+        }
+    }
+}
+
+impl private::SealedType for L {}
+
+/// Maps with string keys, such as nested documents.
+pub struct M(());
+
+impl Type for M {
+    const NAME: &'static str = "M";
+
+    type Raw = HashMap<String, AttributeValue>;
+
+    fn to_attribute_value(raw: Self::Raw) -> AttributeValue {
+        AttributeValue::M(raw)
+    }
+
+    fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
+        match value {
+            AttributeValue::M(m) => Ok(m),
+            value => Err(value),
+        }
+    }
+}
+
+impl private::SealedType for M {}
+
 pub struct Any(());
 
-impl Type for Any { 
+impl Type for Any {
     const NAME: &'static str = "<any>";
 
     type Raw = AttributeValue;
-    
+
     fn to_attribute_value(raw: Self::Raw) -> AttributeValue {
         raw
     }
-    
+
     fn from_attribute_value(value: AttributeValue) -> Result<Self::Raw, AttributeValue> {
         Ok(value)
-    } 
+    }
 }
 
 impl private::SealedType for Any {}
 impl private::SealedKeyType for Any {}
 
-
 // primitive impls
 
 impl<V: Serialize + ?Sized> Serialize for &V {
@@ -159,6 +444,24 @@ impl<V: Serialize + ?Sized> Serialize for &V {
     fn serialize_raw(&self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
         (**self).serialize_raw()
     }
+
+    fn serialize(&self) -> Result<AttributeValue, SerializeError> {
+        (**self).serialize()
+    }
+
+    fn serialize_attribute(
+        &self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        (**self).serialize_attribute(policy)
+    }
+
+    fn serialize_owned_attribute(
+        self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        (*self).serialize_attribute(policy)
+    }
 }
 
 impl<V: Serialize + ?Sized> Serialize for &mut V {
@@ -167,6 +470,24 @@ impl<V: Serialize + ?Sized> Serialize for &mut V {
     fn serialize_raw(&self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
         (**self).serialize_raw()
     }
+
+    fn serialize(&self) -> Result<AttributeValue, SerializeError> {
+        (**self).serialize()
+    }
+
+    fn serialize_attribute(
+        &self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        (**self).serialize_attribute(policy)
+    }
+
+    fn serialize_owned_attribute(
+        self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        (*self).serialize_attribute(policy)
+    }
 }
 
 impl<V: Serialize + ?Sized> Serialize for Box<V> {
@@ -175,6 +496,24 @@ impl<V: Serialize + ?Sized> Serialize for Box<V> {
     fn serialize_raw(&self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
         (**self).serialize_raw()
     }
+
+    fn serialize(&self) -> Result<AttributeValue, SerializeError> {
+        (**self).serialize()
+    }
+
+    fn serialize_attribute(
+        &self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        (**self).serialize_attribute(policy)
+    }
+
+    fn serialize_owned_attribute(
+        self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        (*self).serialize_attribute(policy)
+    }
 }
 
 impl<V: Serialize + ?Sized> Serialize for Arc<V> {
@@ -183,6 +522,24 @@ impl<V: Serialize + ?Sized> Serialize for Arc<V> {
     fn serialize_raw(&self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
         (**self).serialize_raw()
     }
+
+    fn serialize(&self) -> Result<AttributeValue, SerializeError> {
+        (**self).serialize()
+    }
+
+    fn serialize_attribute(
+        &self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        (**self).serialize_attribute(policy)
+    }
+
+    fn serialize_owned_attribute(
+        self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        (*self).serialize_attribute(policy)
+    }
 }
 
 impl<V: Deserialize> Deserialize for Box<V> {
@@ -191,6 +548,17 @@ impl<V: Deserialize> Deserialize for Box<V> {
     fn deserialize_owned_raw(value: <Self::Type as Type>::Raw) -> Result<Self, DeserializeError> {
         Ok(Box::new(V::deserialize_owned_raw(value)?))
     }
+
+    fn deserialize_owned(value: AttributeValue) -> Result<Self, DeserializeError> {
+        Ok(Box::new(V::deserialize_owned(value)?))
+    }
+
+    fn deserialize_attribute(
+        value: Option<AttributeValue>,
+        policy: &Policy,
+    ) -> Result<Option<Self>, DeserializeError> {
+        Ok(V::deserialize_attribute(value, policy)?.map(Box::new))
+    }
 }
 
 impl<V: Deserialize> Deserialize for Arc<V> {
@@ -199,6 +567,17 @@ impl<V: Deserialize> Deserialize for Arc<V> {
     fn deserialize_owned_raw(value: <Self::Type as Type>::Raw) -> Result<Self, DeserializeError> {
         Ok(Arc::new(V::deserialize_owned_raw(value)?))
     }
+
+    fn deserialize_owned(value: AttributeValue) -> Result<Self, DeserializeError> {
+        Ok(Arc::new(V::deserialize_owned(value)?))
+    }
+
+    fn deserialize_attribute(
+        value: Option<AttributeValue>,
+        policy: &Policy,
+    ) -> Result<Option<Self>, DeserializeError> {
+        Ok(V::deserialize_attribute(value, policy)?.map(Arc::new))
+    }
 }
 
 // S
@@ -231,62 +610,82 @@ impl Deserialize for String {
     }
 }
 
-// N
+// B
 
-// Any
+impl Serialize for Blob {
+    type Type = B;
 
-impl Serialize for AttributeValue {
-    type Type = Any;
-    
-    fn serialize_raw(&self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
-        Ok(self.clone())
+    fn serialize_raw(&self) -> Result<Vec<u8>, SerializeError> {
+        Ok(self.as_ref().to_vec())
     }
 
-    fn serialize_owned_raw(self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
-        Ok(self)
+    fn serialize_owned_raw(self) -> Result<Vec<u8>, SerializeError> {
+        Ok(self.into_inner())
     }
 }
 
-impl Deserialize for AttributeValue {
-    type Type = Any;
+impl Deserialize for Blob {
+    type Type = B;
 
-    fn deserialize_owned_raw(raw: <Self::Type as Type>::Raw) -> Result<Self, DeserializeError> {
+    fn deserialize_owned_raw(raw: Vec<u8>) -> Result<Self, DeserializeError> {
+        Ok(Blob::new(raw))
+    }
+}
+
+// BOOL
+
+impl Serialize for bool {
+    type Type = BOOL;
+
+    fn serialize_raw(&self) -> Result<bool, SerializeError> {
+        Ok(*self)
+    }
+}
+
+impl Deserialize for bool {
+    type Type = BOOL;
+
+    fn deserialize_owned_raw(raw: bool) -> Result<Self, DeserializeError> {
         Ok(raw)
     }
 }
 
-impl<K: Serialize<Type = S>, V: Serialize, H> Serialize for HashMap<K, V, H> {
-    type Type = Any; // XX: M
+// NULL
+
+impl Serialize for () {
+    type Type = NULL;
+
+    fn serialize_raw(&self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl Deserialize for () {
+    type Type = NULL;
+
+    fn deserialize_owned_raw(_raw: ()) -> Result<Self, DeserializeError> {
+        Ok(())
+    }
+}
+
+// Any
+
+impl Serialize for AttributeValue {
+    type Type = Any;
 
     fn serialize_raw(&self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
-        Ok(AttributeValue::M(
-            self.iter()
-                .map(|(k, v)| Ok((k.serialize_raw()?, v.serialize()?)))
-                .collect::<Result<_, SerializeError>>()?,
-        ))
+        Ok(self.clone())
     }
 
-    fn serialize_owned_raw(self) -> Result<<Self::Type as Type>::Raw, SerializeError> where Self: Sized {
-        Ok(AttributeValue::M(
-            self.into_iter()
-                .map(|(k, v)| Ok((k.serialize_owned_raw()?, v.serialize_owned()?)))
-                .collect::<Result<_, SerializeError>>()?,
-        ))
+    fn serialize_owned_raw(self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
+        Ok(self)
     }
 }
 
-impl<K: Deserialize<Type = S> + Eq + Hash, V: Deserialize, H: BuildHasher + Default> Deserialize
-    for HashMap<K, V, H>
-{
-    type Type = Any; // XX: M
+impl Deserialize for AttributeValue {
+    type Type = Any;
 
     fn deserialize_owned_raw(raw: <Self::Type as Type>::Raw) -> Result<Self, DeserializeError> {
-        match raw {
-            AttributeValue::M(m) => m
-                .into_iter()
-                .map(|(k, v)| Ok((K::deserialize_owned_raw(k)?, V::deserialize_owned(v)?)))
-                .collect::<Result<_, DeserializeError>>(),
-            v => Err(DeserializeError::unexpected_value_type("M", v)),
-        }
+        Ok(raw)
     }
-}
\ No newline at end of file
+}
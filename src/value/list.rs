@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::error::{DeserializeError, SerializeError};
+
+use super::{Deserialize, Serialize, L};
+
+fn serialize_elements<'a, T: Serialize + 'a>(
+    elements: impl Iterator<Item = &'a T>,
+) -> Result<Vec<AttributeValue>, SerializeError> {
+    elements
+        .enumerate()
+        .map(|(i, v)| v.serialize().map_err(|e| e.at_index(i)))
+        .collect()
+}
+
+fn serialize_owned_elements<T: Serialize>(
+    elements: impl Iterator<Item = T>,
+) -> Result<Vec<AttributeValue>, SerializeError> {
+    elements
+        .enumerate()
+        .map(|(i, v)| v.serialize_owned().map_err(|e| e.at_index(i)))
+        .collect()
+}
+
+fn deserialize_elements<T, C>(raw: Vec<AttributeValue>) -> Result<C, DeserializeError>
+where
+    T: Deserialize,
+    C: FromIterator<T>,
+{
+    raw.into_iter()
+        .enumerate()
+        .map(|(i, v)| T::deserialize_owned(v).map_err(|e| e.at_index(i)))
+        .collect()
+}
+
+fn check_length(raw: &[AttributeValue], expected: usize) -> Result<(), DeserializeError> {
+    if raw.len() == expected {
+        Ok(())
+    } else {
+        Err(DeserializeError::invalid(format_args!(
+            "expected a list of {expected} elements, found {}",
+            raw.len()
+        )))
+    }
+}
+
+impl<T: Serialize> Serialize for [T] {
+    type Type = L;
+
+    fn serialize_raw(&self) -> Result<Vec<AttributeValue>, SerializeError> {
+        serialize_elements(self.iter())
+    }
+}
+
+impl<T: Serialize> Serialize for Vec<T> {
+    type Type = L;
+
+    fn serialize_raw(&self) -> Result<Vec<AttributeValue>, SerializeError> {
+        serialize_elements(self.iter())
+    }
+
+    fn serialize_owned_raw(self) -> Result<Vec<AttributeValue>, SerializeError> {
+        serialize_owned_elements(self.into_iter())
+    }
+}
+
+impl<T: Deserialize> Deserialize for Vec<T> {
+    type Type = L;
+
+    fn deserialize_owned_raw(raw: Vec<AttributeValue>) -> Result<Self, DeserializeError> {
+        deserialize_elements(raw)
+    }
+}
+
+impl<T: Serialize> Serialize for VecDeque<T> {
+    type Type = L;
+
+    fn serialize_raw(&self) -> Result<Vec<AttributeValue>, SerializeError> {
+        serialize_elements(self.iter())
+    }
+
+    fn serialize_owned_raw(self) -> Result<Vec<AttributeValue>, SerializeError> {
+        serialize_owned_elements(self.into_iter())
+    }
+}
+
+impl<T: Deserialize> Deserialize for VecDeque<T> {
+    type Type = L;
+
+    fn deserialize_owned_raw(raw: Vec<AttributeValue>) -> Result<Self, DeserializeError> {
+        deserialize_elements(raw)
+    }
+}
+
+impl<T: Deserialize> Deserialize for Box<[T]> {
+    type Type = L;
+
+    fn deserialize_owned_raw(raw: Vec<AttributeValue>) -> Result<Self, DeserializeError> {
+        deserialize_elements(raw)
+    }
+}
+
+impl<T: Serialize, const LEN: usize> Serialize for [T; LEN] {
+    type Type = L;
+
+    fn serialize_raw(&self) -> Result<Vec<AttributeValue>, SerializeError> {
+        serialize_elements(self.iter())
+    }
+
+    fn serialize_owned_raw(self) -> Result<Vec<AttributeValue>, SerializeError> {
+        serialize_owned_elements(self.into_iter())
+    }
+}
+
+impl<T: Deserialize, const LEN: usize> Deserialize for [T; LEN] {
+    type Type = L;
+
+    fn deserialize_owned_raw(raw: Vec<AttributeValue>) -> Result<Self, DeserializeError> {
+        check_length(&raw, LEN)?;
+        let elements: Vec<T> = deserialize_elements(raw)?;
+        Ok(elements
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("length checked above")))
+    }
+}
+
+macro_rules! impl_tuple {
+    ($len:literal => $($element:ident $index:tt),+) => {
+        impl<$($element: Serialize),+> Serialize for ($($element,)+) {
+            type Type = L;
+
+            fn serialize_raw(&self) -> Result<Vec<AttributeValue>, SerializeError> {
+                Ok(vec![$(self.$index.serialize().map_err(|e| e.at_index($index))?),+])
+            }
+
+            fn serialize_owned_raw(self) -> Result<Vec<AttributeValue>, SerializeError> {
+                Ok(vec![$(self.$index.serialize_owned().map_err(|e| e.at_index($index))?),+])
+            }
+        }
+
+        impl<$($element: Deserialize),+> Deserialize for ($($element,)+) {
+            type Type = L;
+
+            fn deserialize_owned_raw(raw: Vec<AttributeValue>) -> Result<Self, DeserializeError> {
+                check_length(&raw, $len)?;
+                let mut raw = raw.into_iter();
+                Ok(($(
+                    $element::deserialize_owned(raw.next().expect("length checked above"))
+                        .map_err(|e| e.at_index($index))?,
+                )+))
+            }
+        }
+    };
+}
+
+impl_tuple!(1 => T0 0);
+impl_tuple!(2 => T0 0, T1 1);
+impl_tuple!(3 => T0 0, T1 1, T2 2);
+impl_tuple!(4 => T0 0, T1 1, T2 2, T3 3);
+impl_tuple!(5 => T0 0, T1 1, T2 2, T3 3, T4 4);
+impl_tuple!(6 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5);
+impl_tuple!(7 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+impl_tuple!(8 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+impl_tuple!(9 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8);
+impl_tuple!(10 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9);
+impl_tuple!(11 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10);
+impl_tuple!(12 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11);
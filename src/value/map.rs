@@ -0,0 +1,132 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::error::{DeserializeError, SerializeError};
+
+use super::{Deserialize, Serialize, M, S};
+
+fn serialize_entries<'a, K, V>(
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+) -> Result<HashMap<String, AttributeValue>, SerializeError>
+where
+    K: Serialize<Type = S> + 'a,
+    V: Serialize + 'a,
+{
+    entries
+        .map(|(k, v)| {
+            let k = k.serialize_raw()?;
+            let v = v.serialize().map_err(|e| e.at_field(&k))?;
+            Ok((k, v))
+        })
+        .collect()
+}
+
+fn serialize_owned_entries<K, V>(
+    entries: impl Iterator<Item = (K, V)>,
+) -> Result<HashMap<String, AttributeValue>, SerializeError>
+where
+    K: Serialize<Type = S>,
+    V: Serialize,
+{
+    entries
+        .map(|(k, v)| {
+            let k = k.serialize_owned_raw()?;
+            let v = v.serialize_owned().map_err(|e| e.at_field(&k))?;
+            Ok((k, v))
+        })
+        .collect()
+}
+
+fn deserialize_entries<K, V, C>(raw: HashMap<String, AttributeValue>) -> Result<C, DeserializeError>
+where
+    K: Deserialize<Type = S>,
+    V: Deserialize,
+    C: FromIterator<(K, V)>,
+{
+    raw.into_iter()
+        .map(|(k, v)| {
+            let v = V::deserialize_owned(v).map_err(|e| e.at_field(&k))?;
+            Ok((K::deserialize_owned_raw(k)?, v))
+        })
+        .collect()
+}
+
+impl<K: Serialize<Type = S>, V: Serialize, H> Serialize for HashMap<K, V, H> {
+    type Type = M;
+
+    fn serialize_raw(&self) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+        serialize_entries(self.iter())
+    }
+
+    fn serialize_owned_raw(self) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+        serialize_owned_entries(self.into_iter())
+    }
+}
+
+impl<K, V, H> Deserialize for HashMap<K, V, H>
+where
+    K: Deserialize<Type = S> + Eq + Hash,
+    V: Deserialize,
+    H: BuildHasher + Default,
+{
+    type Type = M;
+
+    fn deserialize_owned_raw(
+        raw: HashMap<String, AttributeValue>,
+    ) -> Result<Self, DeserializeError> {
+        deserialize_entries(raw)
+    }
+}
+
+impl<K: Serialize<Type = S>, V: Serialize> Serialize for BTreeMap<K, V> {
+    type Type = M;
+
+    fn serialize_raw(&self) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+        serialize_entries(self.iter())
+    }
+
+    fn serialize_owned_raw(self) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+        serialize_owned_entries(self.into_iter())
+    }
+}
+
+impl<K: Deserialize<Type = S> + Ord, V: Deserialize> Deserialize for BTreeMap<K, V> {
+    type Type = M;
+
+    fn deserialize_owned_raw(
+        raw: HashMap<String, AttributeValue>,
+    ) -> Result<Self, DeserializeError> {
+        deserialize_entries(raw)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K: Serialize<Type = S>, V: Serialize, H> Serialize for indexmap::IndexMap<K, V, H> {
+    type Type = M;
+
+    fn serialize_raw(&self) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+        serialize_entries(self.iter())
+    }
+
+    fn serialize_owned_raw(self) -> Result<HashMap<String, AttributeValue>, SerializeError> {
+        serialize_owned_entries(self.into_iter())
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V, H> Deserialize for indexmap::IndexMap<K, V, H>
+where
+    K: Deserialize<Type = S> + Eq + Hash,
+    V: Deserialize,
+    H: BuildHasher + Default,
+{
+    type Type = M;
+
+    fn deserialize_owned_raw(
+        raw: HashMap<String, AttributeValue>,
+    ) -> Result<Self, DeserializeError> {
+        deserialize_entries(raw)
+    }
+}
@@ -0,0 +1,628 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Write};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+use std::str::FromStr;
+
+use crate::error::{DeserializeError, NumberError, SerializeError};
+
+use super::{Deserialize, Serialize, N};
+
+const MAX_DIGITS: usize = 38;
+const MIN_EXPONENT: i64 = -130;
+const MAX_EXPONENT: i64 = 125;
+
+/// An arbitrary-precision decimal number with exactly the precision and range of DynamoDB's `N`
+/// type: up to 38 significant digits, and magnitudes from 1E-130 up to (but excluding) 1E+126.
+///
+/// Numbers are always kept in a canonical form, so two `Number`s compare equal exactly when they
+/// represent the same value (`"1.50"` and `"15E-1"` parse to equal numbers).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Number {
+    negative: bool,
+    /// Significant digits with no leading or trailing zeros; empty for zero.
+    digits: String,
+    /// The value is `digits × 10^exponent`.
+    exponent: i32,
+}
+
+impl Number {
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+
+    /// The power of ten of the leading digit, as in scientific notation.
+    fn scientific_exponent(&self) -> i64 {
+        self.digits.len() as i64 + self.exponent as i64 - 1
+    }
+
+    /// Renders this number without an exponent, however many zeros that takes.
+    fn to_plain_string(&self) -> String {
+        let mut s = String::new();
+        self.write_plain(&mut s)
+            .expect("writing to a String can't fail");
+        s
+    }
+
+    /// Renders this number as a plain integer, or `None` if it has a fractional part.
+    fn to_integer_string(&self) -> Option<String> {
+        self.is_integer().then(|| self.to_plain_string())
+    }
+
+    fn write_plain(&self, f: &mut impl Write) -> fmt::Result {
+        if self.is_zero() {
+            return f.write_char('0');
+        }
+        if self.negative {
+            f.write_char('-')?;
+        }
+
+        let point = self.digits.len() as i64 + self.exponent as i64;
+        if self.exponent >= 0 {
+            f.write_str(&self.digits)?;
+            (0..self.exponent).try_for_each(|_| f.write_char('0'))
+        } else if point > 0 {
+            let (int, frac) = self.digits.split_at(point as usize);
+            write!(f, "{int}.{frac}")
+        } else {
+            f.write_str("0.")?;
+            (0..-point).try_for_each(|_| f.write_char('0'))?;
+            f.write_str(&self.digits)
+        }
+    }
+
+    fn write_scientific(&self, f: &mut impl Write) -> fmt::Result {
+        if self.negative {
+            f.write_char('-')?;
+        }
+
+        let (first, rest) = self.digits.split_at(1);
+        f.write_str(first)?;
+        if !rest.is_empty() {
+            write!(f, ".{rest}")?;
+        }
+        write!(f, "E{:+}", self.scientific_exponent())
+    }
+}
+
+impl FromStr for Number {
+    type Err = NumberError;
+
+    /// Parses the textual form DynamoDB uses for `N` values: an optionally signed decimal with an
+    /// optional fraction and exponent (`-12.5E+3`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, s) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(i) => (
+                &s[..i],
+                s[i + 1..]
+                    .parse::<i64>()
+                    .map_err(|_| NumberError::syntax())?,
+            ),
+            None => (s, 0),
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if int.is_empty() && frac.is_empty() {
+            return Err(NumberError::syntax());
+        }
+        if !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+            return Err(NumberError::syntax());
+        }
+
+        let mut digits = String::with_capacity(int.len() + frac.len());
+        digits.push_str(int);
+        digits.push_str(frac);
+
+        let trailing = digits.len() - digits.trim_end_matches('0').len();
+        digits.truncate(digits.len() - trailing);
+        let leading = digits.len() - digits.trim_start_matches('0').len();
+        digits.drain(..leading);
+
+        if digits.is_empty() {
+            return Ok(Self {
+                negative: false,
+                digits,
+                exponent: 0,
+            });
+        }
+        if digits.len() > MAX_DIGITS {
+            return Err(NumberError::precision());
+        }
+
+        let exponent = exponent
+            .checked_sub(frac.len() as i64)
+            .and_then(|e| e.checked_add(trailing as i64))
+            .ok_or_else(NumberError::range)?;
+        let scientific = digits.len() as i64 - 1 + exponent;
+        if !(MIN_EXPONENT..=MAX_EXPONENT).contains(&scientific) {
+            return Err(NumberError::range());
+        }
+
+        Ok(Self {
+            negative,
+            digits,
+            exponent: exponent as i32,
+        })
+    }
+}
+
+impl Display for Number {
+    /// Writes the canonical form of this number: plain decimal notation for moderate magnitudes,
+    /// and scientific notation (`1.5E+30`) when that would otherwise need many padding zeros.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() || (-7..21).contains(&self.scientific_exponent()) {
+            self.write_plain(f)
+        } else {
+            self.write_scientific(f)
+        }
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let signum = |n: &Number| match (n.is_zero(), n.negative) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        };
+
+        signum(self).cmp(&signum(other)).then_with(|| {
+            let magnitude = self
+                .scientific_exponent()
+                .cmp(&other.scientific_exponent())
+                .then_with(|| self.digits.cmp(&other.digits));
+            if self.negative {
+                magnitude.reverse()
+            } else {
+                magnitude
+            }
+        })
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Serialize for Number {
+    type Type = N;
+
+    fn serialize_raw(&self) -> Result<String, SerializeError> {
+        Ok(self.to_string())
+    }
+}
+
+impl Deserialize for Number {
+    type Type = N;
+
+    fn deserialize_owned_raw(raw: String) -> Result<Self, DeserializeError> {
+        raw.parse().map_err(DeserializeError::invalid)
+    }
+}
+
+// integers
+
+macro_rules! impl_from_integer {
+    ($($int:ty),* $(,)?) => {
+        $(
+            impl From<$int> for Number {
+                fn from(value: $int) -> Self {
+                    value.to_string().parse().expect("integers of 64 bits or less fit in a Number")
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl TryFrom<u128> for Number {
+    type Error = NumberError;
+
+    fn try_from(value: u128) -> Result<Self, Self::Error> {
+        value.to_string().parse()
+    }
+}
+
+impl TryFrom<i128> for Number {
+    type Error = NumberError;
+
+    fn try_from(value: i128) -> Result<Self, Self::Error> {
+        value.to_string().parse()
+    }
+}
+
+fn parse_integer<I: FromStr>(raw: &str, name: &str) -> Result<I, DeserializeError> {
+    if let Ok(i) = raw.parse() {
+        return Ok(i);
+    }
+
+    let number: Number = raw.parse().map_err(DeserializeError::invalid)?;
+    let integer = number.to_integer_string().ok_or_else(|| {
+        DeserializeError::invalid(format_args!("value is not an integer, expected {name}"))
+    })?;
+    integer
+        .parse()
+        .map_err(|_| DeserializeError::invalid(format_args!("value is out of range for {name}")))
+}
+
+macro_rules! impl_integer {
+    ($($int:ty),* $(,)?) => {
+        $(
+            impl Serialize for $int {
+                type Type = N;
+
+                fn serialize_raw(&self) -> Result<String, SerializeError> {
+                    Ok(self.to_string())
+                }
+            }
+
+            impl_integer!(@deserialize $int);
+        )*
+    };
+    (@checked $($int:ty),* $(,)?) => {
+        $(
+            impl Serialize for $int {
+                type Type = N;
+
+                fn serialize_raw(&self) -> Result<String, SerializeError> {
+                    Number::try_from(*self)
+                        .map(|n| n.to_string())
+                        .map_err(SerializeError::from)
+                }
+            }
+
+            impl_integer!(@deserialize $int);
+        )*
+    };
+    (@deserialize $int:ty) => {
+        impl Deserialize for $int {
+            type Type = N;
+
+            fn deserialize_owned_raw(raw: String) -> Result<Self, DeserializeError> {
+                parse_integer(&raw, stringify!($int))
+            }
+        }
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+// 128-bit integers can exceed DynamoDB's 38 digits of precision.
+impl_integer!(@checked u128, i128);
+
+macro_rules! impl_non_zero {
+    ($($non_zero:ident($int:ty)),* $(,)?) => {
+        $(
+            impl Serialize for $non_zero {
+                type Type = N;
+
+                fn serialize_raw(&self) -> Result<String, SerializeError> {
+                    self.get().serialize_raw()
+                }
+            }
+
+            impl Deserialize for $non_zero {
+                type Type = N;
+
+                fn deserialize_owned_raw(raw: String) -> Result<Self, DeserializeError> {
+                    let int: $int = parse_integer(&raw, stringify!($non_zero))?;
+                    $non_zero::new(int).ok_or_else(|| {
+                        DeserializeError::invalid(concat!("value is zero, expected ", stringify!($non_zero)))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_non_zero!(
+    NonZeroU8(u8),
+    NonZeroU16(u16),
+    NonZeroU32(u32),
+    NonZeroU64(u64),
+    NonZeroU128(u128),
+    NonZeroUsize(usize),
+    NonZeroI8(i8),
+    NonZeroI16(i16),
+    NonZeroI32(i32),
+    NonZeroI64(i64),
+    NonZeroI128(i128),
+    NonZeroIsize(isize),
+);
+
+// floats
+
+macro_rules! impl_float {
+    ($($float:ty),* $(,)?) => {
+        $(
+            impl TryFrom<$float> for Number {
+                type Error = NumberError;
+
+                fn try_from(value: $float) -> Result<Self, Self::Error> {
+                    if !value.is_finite() {
+                        return Err(NumberError::not_finite());
+                    }
+                    // `{:e}` writes the shortest digits that round-trip back to the same float.
+                    format!("{value:e}").parse()
+                }
+            }
+
+            impl Serialize for $float {
+                type Type = N;
+
+                fn serialize_raw(&self) -> Result<String, SerializeError> {
+                    Number::try_from(*self)
+                        .map(|n| n.to_string())
+                        .map_err(SerializeError::from)
+                }
+            }
+
+            impl Deserialize for $float {
+                type Type = N;
+
+                fn deserialize_owned_raw(raw: String) -> Result<Self, DeserializeError> {
+                    let float: $float = raw
+                        .parse()
+                        .map_err(|_| DeserializeError::invalid(NumberError::syntax()))?;
+                    if float.is_finite() {
+                        Ok(float)
+                    } else {
+                        Err(DeserializeError::invalid(concat!("value is out of range for ", stringify!($float))))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+// third-party numbers
+
+#[cfg(feature = "rust_decimal")]
+impl Serialize for rust_decimal::Decimal {
+    type Type = N;
+
+    fn serialize_raw(&self) -> Result<String, SerializeError> {
+        Ok(self.normalize().to_string())
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl Deserialize for rust_decimal::Decimal {
+    type Type = N;
+
+    fn deserialize_owned_raw(raw: String) -> Result<Self, DeserializeError> {
+        let number: Number = raw.parse().map_err(DeserializeError::invalid)?;
+        rust_decimal::Decimal::from_str_exact(&number.to_plain_string())
+            .map_err(|_| DeserializeError::invalid("value is out of range for Decimal"))
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl Serialize for num_bigint::BigInt {
+    type Type = N;
+
+    fn serialize_raw(&self) -> Result<String, SerializeError> {
+        self.to_string()
+            .parse::<Number>()
+            .map(|n| n.to_string())
+            .map_err(SerializeError::from)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl Deserialize for num_bigint::BigInt {
+    type Type = N;
+
+    fn deserialize_owned_raw(raw: String) -> Result<Self, DeserializeError> {
+        let number: Number = raw.parse().map_err(DeserializeError::invalid)?;
+        let integer = number
+            .to_integer_string()
+            .ok_or_else(|| DeserializeError::invalid("value is not an integer, expected BigInt"))?;
+        integer.parse().map_err(DeserializeError::invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use crate::error::{DeserializeErrorKind, NumberErrorKind, SerializeErrorKind};
+
+    use super::*;
+
+    fn parse_err(s: &str) -> NumberErrorKind {
+        s.parse::<Number>().unwrap_err().kind()
+    }
+
+    fn serialize_err(value: impl Serialize<Type = N>) -> NumberErrorKind {
+        match value.serialize_raw().unwrap_err().kind() {
+            SerializeErrorKind::InvalidNumber(e) => e.kind(),
+            kind => panic!("expected an invalid number, got {kind:?}"),
+        }
+    }
+
+    fn deserialize_err<D: Deserialize<Type = N> + std::fmt::Debug>(raw: &str) -> String {
+        match D::deserialize_owned_raw(raw.to_owned()).unwrap_err().kind() {
+            DeserializeErrorKind::Invalid(message) => message.clone(),
+            kind => panic!("expected an invalid value, got {kind:?}"),
+        }
+    }
+
+    #[test]
+    fn canonical_form() {
+        let n = |s: &str| s.parse::<Number>().unwrap();
+        assert_eq!(n("1.50"), n("15E-1"));
+        assert_eq!(n("+0.0"), n("-0"));
+        assert_eq!(n("-0").to_string(), "0");
+        assert_eq!(n(".5").to_string(), "0.5");
+        assert_eq!(n("5.").to_string(), "5");
+        assert_eq!(n("-12.5E+3").to_string(), "-12500");
+        assert_eq!(n("0.0000001").to_string(), "0.0000001");
+        assert_eq!(n("0.00000001").to_string(), "1E-8");
+        assert_eq!(n("1E20").to_string(), "100000000000000000000");
+        assert_eq!(n("1.5E21").to_string(), "1.5E+21");
+        assert!(n("-1") < n("0") && n("0") < n("0.001") && n("99") < n("100"));
+        assert!(n("-100") < n("-99"));
+    }
+
+    #[test]
+    fn syntax() {
+        for s in [
+            "", ".", "+", "-", "e5", "1e", "1E+", "abc", "--1", "1.2.3", "1_000", " 1", "0x10",
+        ] {
+            assert_eq!(parse_err(s), NumberErrorKind::Syntax, "{s:?}");
+        }
+    }
+
+    #[test]
+    fn precision() {
+        let max = "9".repeat(MAX_DIGITS);
+        assert!(max.parse::<Number>().is_ok());
+        assert!(format!("0.{max}").parse::<Number>().is_ok());
+        assert_eq!(parse_err(&format!("1{max}")), NumberErrorKind::Precision);
+        assert_eq!(parse_err(&format!("1.{max}")), NumberErrorKind::Precision);
+        // Leading and trailing zeros aren't significant.
+        assert!(format!("000{max}000").parse::<Number>().is_ok());
+    }
+
+    #[test]
+    fn range() {
+        assert!("1E-130".parse::<Number>().is_ok());
+        assert!("10E-131".parse::<Number>().is_ok());
+        assert_eq!(parse_err("1E-131"), NumberErrorKind::Range);
+        assert_eq!(parse_err("0.9E-130"), NumberErrorKind::Range);
+        assert!(format!("{}E+88", "9".repeat(MAX_DIGITS))
+            .parse::<Number>()
+            .is_ok());
+        assert_eq!(parse_err("1E+126"), NumberErrorKind::Range);
+        assert_eq!(parse_err("-1E+126"), NumberErrorKind::Range);
+        assert_eq!(parse_err("1E9223372036854775807"), NumberErrorKind::Range);
+        // Zero is in range with any exponent.
+        assert!("0E+1000".parse::<Number>().unwrap().is_zero());
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(u64::MAX.serialize_raw().unwrap(), "18446744073709551615");
+        assert_eq!(i8::deserialize_owned_raw("-128".into()).unwrap(), -128);
+        assert_eq!(u32::deserialize_owned_raw("1.5E2".into()).unwrap(), 150);
+        assert_eq!(u32::deserialize_owned_raw("1000E-3".into()).unwrap(), 1);
+        assert_eq!(deserialize_err::<u8>("256"), "value is out of range for u8");
+        assert_eq!(deserialize_err::<u8>("-1"), "value is out of range for u8");
+        assert_eq!(
+            deserialize_err::<i64>("1.5"),
+            "value is not an integer, expected i64"
+        );
+        assert_eq!(
+            deserialize_err::<u64>("1E+30"),
+            "value is out of range for u64"
+        );
+        assert_eq!(
+            deserialize_err::<NonZeroU32>("0"),
+            "value is zero, expected NonZeroU32"
+        );
+        assert_eq!(
+            NonZeroU32::deserialize_owned_raw("7".into()).unwrap().get(),
+            7
+        );
+    }
+
+    #[test]
+    fn wide_integers() {
+        assert_eq!(10_u128.pow(38).serialize_raw().unwrap(), "1E+38");
+        assert_eq!(serialize_err(u128::MAX), NumberErrorKind::Precision);
+        assert_eq!(serialize_err(i128::MIN), NumberErrorKind::Precision);
+        assert!(Number::try_from(i128::MAX / 10).is_ok());
+        assert_eq!(
+            u128::deserialize_owned_raw("1E+38".into()).unwrap(),
+            10_u128.pow(38)
+        );
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(0.1_f64.serialize_raw().unwrap(), "0.1");
+        assert_eq!(1.5_f32.serialize_raw().unwrap(), "1.5");
+        assert_eq!((-2.5e-10_f64).serialize_raw().unwrap(), "-2.5E-10");
+        assert_eq!(1e100_f64.serialize_raw().unwrap(), "1E+100");
+        assert_eq!(0.0_f64.serialize_raw().unwrap(), "0");
+        assert_eq!(serialize_err(f64::NAN), NumberErrorKind::NotFinite);
+        assert_eq!(serialize_err(f32::INFINITY), NumberErrorKind::NotFinite);
+        assert_eq!(serialize_err(1e200_f64), NumberErrorKind::Range);
+        assert_eq!(serialize_err(f64::MIN_POSITIVE), NumberErrorKind::Range);
+
+        for f in [0.1, 1.0 / 3.0, 123456.789, f64::EPSILON, -1e125] {
+            let raw = f.serialize_raw().unwrap();
+            assert_eq!(f64::deserialize_owned_raw(raw).unwrap(), f);
+        }
+        assert_eq!(
+            deserialize_err::<f32>("1E+100"),
+            "value is out of range for f32"
+        );
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn rust_decimal() {
+        use rust_decimal::Decimal;
+
+        assert_eq!(Decimal::new(1500, 3).serialize_raw().unwrap(), "1.5");
+        assert_eq!(
+            Decimal::deserialize_owned_raw("15E-1".into()).unwrap(),
+            Decimal::new(15, 1)
+        );
+        assert_eq!(
+            Decimal::deserialize_owned_raw("-1E+20".into()).unwrap(),
+            Decimal::from_i128_with_scale(-100_000_000_000_000_000_000, 0)
+        );
+        assert_eq!(
+            deserialize_err::<Decimal>("1E+30"),
+            "value is out of range for Decimal"
+        );
+        assert_eq!(
+            deserialize_err::<Decimal>("1E-130"),
+            "value is out of range for Decimal"
+        );
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn num_bigint() {
+        use num_bigint::BigInt;
+
+        let big = BigInt::from(10).pow(40);
+        assert_eq!(big.serialize_raw().unwrap(), "1E+40");
+        assert_eq!(BigInt::deserialize_owned_raw("1E+40".into()).unwrap(), big);
+        assert_eq!(
+            serialize_err(BigInt::from(10).pow(38) + 1),
+            NumberErrorKind::Precision
+        );
+        assert_eq!(
+            serialize_err(BigInt::from(10).pow(126)),
+            NumberErrorKind::Range
+        );
+        assert_eq!(
+            deserialize_err::<BigInt>("1.5"),
+            "value is not an integer, expected BigInt"
+        );
+    }
+}
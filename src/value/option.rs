@@ -0,0 +1,182 @@
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::error::{DeserializeError, SerializeError};
+
+use super::{Deserialize, Nones, Nulls, Optional, Policy, Serialize, Type};
+
+/// `None` is written as `NULL` wherever a full attribute value is produced, and as specified by
+/// the [`Policy`] for top-level attributes.
+impl<T: Serialize> Serialize for Option<T> {
+    type Type = Optional<T::Type>;
+
+    fn serialize_raw(&self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
+        self.as_ref().map(T::serialize_raw).transpose()
+    }
+
+    fn serialize_owned_raw(self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
+        self.map(T::serialize_owned_raw).transpose()
+    }
+
+    fn serialize(&self) -> Result<AttributeValue, SerializeError> {
+        match self {
+            Some(value) => value.serialize(),
+            None => Ok(AttributeValue::Null(true)),
+        }
+    }
+
+    fn serialize_owned(self) -> Result<AttributeValue, SerializeError> {
+        match self {
+            Some(value) => value.serialize_owned(),
+            None => Ok(AttributeValue::Null(true)),
+        }
+    }
+
+    fn serialize_attribute(
+        &self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        match self {
+            Some(value) => value.serialize_attribute(policy),
+            None => Ok(serialize_none(policy)),
+        }
+    }
+
+    fn serialize_owned_attribute(
+        self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        match self {
+            Some(value) => value.serialize_owned_attribute(policy),
+            None => Ok(serialize_none(policy)),
+        }
+    }
+}
+
+fn serialize_none(policy: &Policy) -> Option<AttributeValue> {
+    match policy.nones {
+        Nones::Omit => None,
+        Nones::Null => Some(AttributeValue::Null(true)),
+    }
+}
+
+impl<T: Deserialize> Deserialize for Option<T> {
+    type Type = Optional<T::Type>;
+
+    fn deserialize_owned_raw(raw: <Self::Type as Type>::Raw) -> Result<Self, DeserializeError> {
+        raw.map(T::deserialize_owned_raw).transpose()
+    }
+
+    fn deserialize_owned(value: AttributeValue) -> Result<Self, DeserializeError> {
+        match value {
+            AttributeValue::Null(_) => Ok(None),
+            value => T::deserialize_owned(value).map(Some),
+        }
+    }
+
+    fn deserialize_attribute(
+        value: Option<AttributeValue>,
+        policy: &Policy,
+    ) -> Result<Option<Self>, DeserializeError> {
+        match (value, policy.nulls) {
+            (None, _) | (Some(AttributeValue::Null(_)), Nulls::AsNone) => Ok(Some(None)),
+            (Some(value @ AttributeValue::Null(_)), Nulls::Reject) => Err(
+                DeserializeError::unexpected_value_type(T::Type::NAME, value),
+            ),
+            (value, _) => Ok(T::deserialize_attribute(value, policy)?.map(Some)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::DeserializeErrorKind;
+
+    use super::*;
+
+    #[test]
+    fn serialize() {
+        assert_eq!(
+            Some(5_u8).serialize().unwrap(),
+            AttributeValue::N("5".into())
+        );
+        assert_eq!(None::<u8>.serialize().unwrap(), AttributeValue::Null(true));
+        assert_eq!(
+            None::<u8>.serialize_owned().unwrap(),
+            AttributeValue::Null(true)
+        );
+        assert_eq!(None::<u8>.serialize_raw().unwrap(), None);
+        assert_eq!(
+            vec![Some(1_u8), None].serialize().unwrap(),
+            AttributeValue::L(vec![
+                AttributeValue::N("1".into()),
+                AttributeValue::Null(true)
+            ])
+        );
+    }
+
+    #[test]
+    fn nones_policy() {
+        let omit = Policy::DEFAULT.nones(Nones::Omit);
+        let null = Policy::DEFAULT.nones(Nones::Null);
+        assert_eq!(None::<String>.serialize_attribute(&omit).unwrap(), None);
+        assert_eq!(
+            None::<String>.serialize_owned_attribute(&null).unwrap(),
+            Some(AttributeValue::Null(true))
+        );
+        assert_eq!(
+            Some("a".to_owned()).serialize_attribute(&omit).unwrap(),
+            Some(AttributeValue::S("a".into()))
+        );
+    }
+
+    #[test]
+    fn deserialize() {
+        assert_eq!(
+            Option::<u8>::deserialize_owned(AttributeValue::Null(true)).unwrap(),
+            None
+        );
+        assert_eq!(
+            Option::<u8>::deserialize_owned(AttributeValue::N("7".into())).unwrap(),
+            Some(7)
+        );
+        assert!(matches!(
+            Option::<u8>::deserialize_owned(AttributeValue::S("7".into()))
+                .unwrap_err()
+                .kind(),
+            DeserializeErrorKind::UnexpectedType {
+                expected: "N",
+                actual: "S"
+            }
+        ));
+    }
+
+    #[test]
+    fn nulls_policy() {
+        let as_none = Policy::DEFAULT.nulls(Nulls::AsNone);
+        let reject = Policy::DEFAULT.nulls(Nulls::Reject);
+        let null = || Some(AttributeValue::Null(true));
+
+        assert_eq!(
+            Option::<u8>::deserialize_attribute(None, &reject).unwrap(),
+            Some(None)
+        );
+        assert_eq!(
+            Option::<u8>::deserialize_attribute(null(), &as_none).unwrap(),
+            Some(None)
+        );
+        assert!(matches!(
+            Option::<u8>::deserialize_attribute(null(), &reject)
+                .unwrap_err()
+                .kind(),
+            DeserializeErrorKind::UnexpectedType {
+                expected: "N",
+                actual: "NULL"
+            }
+        ));
+        assert_eq!(
+            Option::<u8>::deserialize_attribute(Some(AttributeValue::N("1".into())), &reject)
+                .unwrap(),
+            Some(Some(1))
+        );
+    }
+}
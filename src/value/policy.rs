@@ -0,0 +1,65 @@
+/// Controls how values that DynamoDB can't store directly are written to, and read back from, top
+/// level item attributes.
+///
+/// The default policy omits empty sets and `None`s from the item, reads an absent set attribute
+/// back as an empty set, and reads a `NULL` attribute back as `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub struct Policy {
+    pub empty_sets: EmptySets,
+    pub nones: Nones,
+    pub nulls: Nulls,
+}
+
+impl Policy {
+    pub const DEFAULT: Self = Self {
+        empty_sets: EmptySets::Omit,
+        nones: Nones::Omit,
+        nulls: Nulls::AsNone,
+    };
+
+    pub const fn empty_sets(self, empty_sets: EmptySets) -> Self {
+        Self { empty_sets, ..self }
+    }
+
+    pub const fn nones(self, nones: Nones) -> Self {
+        Self { nones, ..self }
+    }
+
+    pub const fn nulls(self, nulls: Nulls) -> Self {
+        Self { nulls, ..self }
+    }
+}
+
+/// DynamoDB rejects empty sets, so they need to be written some other way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EmptySets {
+    /// Omit the attribute; an absent attribute is read back as an empty set.
+    #[default]
+    Omit,
+    /// Write `NULL`; a `NULL` attribute is read back as an empty set.
+    Null,
+    /// Fail serialization with a [`SerializeError`](crate::error::SerializeError).
+    Reject,
+}
+
+/// How an `Option` attribute holding `None` is written. An absent attribute is always read back as
+/// `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Nones {
+    /// Omit the attribute.
+    #[default]
+    Omit,
+    /// Write `NULL`.
+    Null,
+}
+
+/// How a `NULL` attribute is read into an `Option`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Nulls {
+    /// Read `NULL` as `None`.
+    #[default]
+    AsNone,
+    /// Fail deserialization, as for any other unexpected value type.
+    Reject,
+}
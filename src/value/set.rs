@@ -0,0 +1,296 @@
+use std::collections::{BTreeSet, HashSet};
+use std::hash::{BuildHasher, Hash};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::error::{DeserializeError, SerializeError, SerializeErrorKind};
+
+use super::{Deserialize, EmptySets, Policy, Serialize, SetElementType, Type};
+
+fn empty_set_error() -> SerializeError {
+    SerializeError::new(SerializeErrorKind::EmptySet)
+}
+
+fn serialize_elements<'a, T>(
+    elements: impl ExactSizeIterator<Item = &'a T>,
+) -> Result<Vec<<T::Type as Type>::Raw>, SerializeError>
+where
+    T: Serialize + 'a,
+{
+    if elements.len() == 0 {
+        return Err(empty_set_error());
+    }
+    elements.map(Serialize::serialize_raw).collect()
+}
+
+fn serialize_owned_elements<T: Serialize>(
+    elements: impl ExactSizeIterator<Item = T>,
+) -> Result<Vec<<T::Type as Type>::Raw>, SerializeError> {
+    if elements.len() == 0 {
+        return Err(empty_set_error());
+    }
+    elements.map(Serialize::serialize_owned_raw).collect()
+}
+
+/// Applies `policy` to a set that's about to be written as an item attribute.
+fn serialize_attribute(
+    is_empty: bool,
+    policy: &Policy,
+    serialize: impl FnOnce() -> Result<AttributeValue, SerializeError>,
+) -> Result<Option<AttributeValue>, SerializeError> {
+    if !is_empty {
+        return serialize().map(Some);
+    }
+    match policy.empty_sets {
+        EmptySets::Omit => Ok(None),
+        EmptySets::Null => Ok(Some(AttributeValue::Null(true))),
+        EmptySets::Reject => Err(empty_set_error()),
+    }
+}
+
+fn deserialize_elements<T, C>(
+    raw: Vec<<T::Type as Type>::Raw>,
+    mut insert: impl FnMut(&mut C, T) -> bool,
+) -> Result<C, DeserializeError>
+where
+    T: Deserialize,
+    C: Default,
+{
+    let mut set = C::default();
+    for element in raw {
+        if !insert(&mut set, T::deserialize_owned_raw(element)?) {
+            return Err(DeserializeError::invalid("set contains duplicate elements"));
+        }
+    }
+    Ok(set)
+}
+
+/// Applies `policy` to a set attribute read from an item.
+fn deserialize_attribute<C: Deserialize + Default>(
+    value: Option<AttributeValue>,
+    policy: &Policy,
+) -> Result<Option<C>, DeserializeError> {
+    match (value, policy.empty_sets) {
+        (None, EmptySets::Omit) | (Some(AttributeValue::Null(_)), EmptySets::Null) => {
+            Ok(Some(C::default()))
+        }
+        (value, _) => value.map(C::deserialize_owned).transpose(),
+    }
+}
+
+impl<T, H> Serialize for HashSet<T, H>
+where
+    T: Serialize,
+    T::Type: SetElementType,
+{
+    type Type = <T::Type as SetElementType>::Set;
+
+    fn serialize_raw(&self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
+        serialize_elements(self.iter())
+    }
+
+    fn serialize_owned_raw(self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
+        serialize_owned_elements(self.into_iter())
+    }
+
+    fn serialize_attribute(
+        &self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        serialize_attribute(self.is_empty(), policy, || self.serialize())
+    }
+
+    fn serialize_owned_attribute(
+        self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        serialize_attribute(self.is_empty(), policy, || self.serialize_owned())
+    }
+}
+
+impl<T, H> Deserialize for HashSet<T, H>
+where
+    T: Deserialize + Eq + Hash,
+    T::Type: SetElementType,
+    H: BuildHasher + Default,
+{
+    type Type = <T::Type as SetElementType>::Set;
+
+    fn deserialize_owned_raw(raw: <Self::Type as Type>::Raw) -> Result<Self, DeserializeError> {
+        deserialize_elements(raw, HashSet::insert)
+    }
+
+    fn deserialize_attribute(
+        value: Option<AttributeValue>,
+        policy: &Policy,
+    ) -> Result<Option<Self>, DeserializeError> {
+        deserialize_attribute(value, policy)
+    }
+}
+
+impl<T> Serialize for BTreeSet<T>
+where
+    T: Serialize,
+    T::Type: SetElementType,
+{
+    type Type = <T::Type as SetElementType>::Set;
+
+    fn serialize_raw(&self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
+        serialize_elements(self.iter())
+    }
+
+    fn serialize_owned_raw(self) -> Result<<Self::Type as Type>::Raw, SerializeError> {
+        serialize_owned_elements(self.into_iter())
+    }
+
+    fn serialize_attribute(
+        &self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        serialize_attribute(self.is_empty(), policy, || self.serialize())
+    }
+
+    fn serialize_owned_attribute(
+        self,
+        policy: &Policy,
+    ) -> Result<Option<AttributeValue>, SerializeError> {
+        serialize_attribute(self.is_empty(), policy, || self.serialize_owned())
+    }
+}
+
+impl<T> Deserialize for BTreeSet<T>
+where
+    T: Deserialize + Ord,
+    T::Type: SetElementType,
+{
+    type Type = <T::Type as SetElementType>::Set;
+
+    fn deserialize_owned_raw(raw: <Self::Type as Type>::Raw) -> Result<Self, DeserializeError> {
+        deserialize_elements(raw, BTreeSet::insert)
+    }
+
+    fn deserialize_attribute(
+        value: Option<AttributeValue>,
+        policy: &Policy,
+    ) -> Result<Option<Self>, DeserializeError> {
+        deserialize_attribute(value, policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::error::DeserializeErrorKind;
+    use crate::value::Number;
+
+    use super::*;
+
+    fn policy(empty_sets: EmptySets) -> Policy {
+        Policy::DEFAULT.empty_sets(empty_sets)
+    }
+
+    #[test]
+    fn serialize() {
+        let set = BTreeSet::from(["b".to_owned(), "a".to_owned()]);
+        assert_eq!(
+            set.serialize().unwrap(),
+            AttributeValue::Ss(vec!["a".into(), "b".into()])
+        );
+        assert_eq!(
+            BTreeSet::from([3_u8, 1]).serialize_owned().unwrap(),
+            AttributeValue::Ns(vec!["1".into(), "3".into()])
+        );
+        assert_eq!(
+            HashSet::<u8>::new().serialize().unwrap_err().kind(),
+            &SerializeErrorKind::EmptySet
+        );
+    }
+
+    #[test]
+    fn empty_set_policy() {
+        let empty = HashSet::<String>::new();
+        assert_eq!(
+            empty.serialize_attribute(&policy(EmptySets::Omit)).unwrap(),
+            None
+        );
+        assert_eq!(
+            empty.serialize_attribute(&policy(EmptySets::Null)).unwrap(),
+            Some(AttributeValue::Null(true))
+        );
+        assert_eq!(
+            empty
+                .serialize_attribute(&policy(EmptySets::Reject))
+                .unwrap_err()
+                .kind(),
+            &SerializeErrorKind::EmptySet
+        );
+        assert_eq!(
+            empty
+                .clone()
+                .serialize_owned_attribute(&policy(EmptySets::Null))
+                .unwrap(),
+            Some(AttributeValue::Null(true))
+        );
+    }
+
+    #[test]
+    fn empty_set_policy_through_wrappers() {
+        let omit = policy(EmptySets::Omit);
+        let empty = HashSet::<String>::new();
+        assert_eq!(
+            Box::new(empty.clone())
+                .serialize_owned_attribute(&omit)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            Arc::new(empty.clone())
+                .serialize_owned_attribute(&omit)
+                .unwrap(),
+            None
+        );
+        assert_eq!((&empty).serialize_owned_attribute(&omit).unwrap(), None);
+    }
+
+    #[test]
+    fn deserialize_rejects_duplicates() {
+        let duplicate = AttributeValue::Ss(vec!["a".into(), "a".into()]);
+        let error = HashSet::<String>::deserialize_owned(duplicate).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &DeserializeErrorKind::Invalid("set contains duplicate elements".into())
+        );
+
+        // DynamoDB treats these as distinct strings, but they're the same number.
+        let duplicate = AttributeValue::Ns(vec!["1".into(), "1.0".into()]);
+        assert!(BTreeSet::<Number>::deserialize_owned(duplicate).is_err());
+    }
+
+    #[test]
+    fn deserialize_attribute_policy() {
+        let read = |value, empty_sets| {
+            BTreeSet::<String>::deserialize_attribute(value, &policy(empty_sets))
+        };
+        let null = || Some(AttributeValue::Null(true));
+
+        assert_eq!(read(None, EmptySets::Omit).unwrap(), Some(BTreeSet::new()));
+        assert_eq!(read(None, EmptySets::Null).unwrap(), None);
+        assert_eq!(read(None, EmptySets::Reject).unwrap(), None);
+        assert_eq!(
+            read(null(), EmptySets::Null).unwrap(),
+            Some(BTreeSet::new())
+        );
+        assert!(matches!(
+            read(null(), EmptySets::Omit).unwrap_err().kind(),
+            DeserializeErrorKind::UnexpectedType {
+                expected: "SS",
+                actual: "NULL"
+            }
+        ));
+        assert_eq!(
+            read(Some(AttributeValue::Ss(vec!["x".into()])), EmptySets::Omit).unwrap(),
+            Some(BTreeSet::from(["x".to_owned()]))
+        );
+    }
+}
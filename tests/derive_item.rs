@@ -0,0 +1,253 @@
+use std::collections::{BTreeSet, HashMap};
+
+use amo::error::{DeserializeError, DeserializeErrorKind, SerializeErrorKind};
+use amo::item::{Deserialize, Serialize};
+use amo::value::{EmptySets, Nones, Policy};
+use aws_sdk_dynamodb::types::AttributeValue;
+
+fn s(s: &str) -> AttributeValue {
+    AttributeValue::S(s.to_owned())
+}
+
+fn n(n: &str) -> AttributeValue {
+    AttributeValue::N(n.to_owned())
+}
+
+fn item<const N: usize>(
+    attributes: [(&str, AttributeValue); N],
+) -> HashMap<String, AttributeValue> {
+    attributes
+        .into_iter()
+        .map(|(name, value)| (name.to_owned(), value))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Default, amo::Item)]
+#[amo(rename_all = "camelCase")]
+struct Profile {
+    user_id: String,
+    #[amo(rename = "n")]
+    display_name: String,
+    login_count: u32,
+    nickname: Option<String>,
+    labels: BTreeSet<String>,
+    #[amo(default)]
+    score: u8,
+    #[amo(default = default_level)]
+    level: u8,
+    #[amo(skip)]
+    cached: bool,
+    #[amo(flatten)]
+    address: Address,
+    #[amo(with = upper)]
+    code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, amo::Item)]
+struct Address {
+    city: String,
+    zip: Option<String>,
+}
+
+fn default_level() -> u8 {
+    7
+}
+
+mod upper {
+    use amo::error::{DeserializeError, SerializeError};
+    use amo::value::{Deserialize, Serialize};
+    use aws_sdk_dynamodb::types::AttributeValue;
+
+    pub fn serialize(value: &str) -> Result<AttributeValue, SerializeError> {
+        value.to_uppercase().serialize_owned()
+    }
+
+    pub fn deserialize(value: AttributeValue) -> Result<String, DeserializeError> {
+        String::deserialize_owned(value).map(|s| s.to_lowercase())
+    }
+}
+
+fn profile() -> Profile {
+    Profile {
+        user_id: "u1".into(),
+        display_name: "Alice".into(),
+        login_count: 3,
+        nickname: None,
+        labels: BTreeSet::new(),
+        score: 1,
+        level: 2,
+        cached: true,
+        address: Address {
+            city: "Oslo".into(),
+            zip: Some("0150".into()),
+        },
+        code: "abc".into(),
+    }
+}
+
+#[test]
+fn serialize() {
+    assert_eq!(
+        profile().serialize_to_map().unwrap(),
+        item([
+            ("userId", s("u1")),
+            ("n", s("Alice")),
+            ("loginCount", n("3")),
+            ("score", n("1")),
+            ("level", n("2")),
+            ("city", s("Oslo")),
+            ("zip", s("0150")),
+            ("code", s("ABC")),
+        ])
+    );
+}
+
+#[test]
+fn round_trip() {
+    let mut profile = profile();
+    profile.nickname = Some("al".into());
+    profile.labels = BTreeSet::from(["admin".to_owned()]);
+    let read = Profile::deserialize_owned_from_map(profile.serialize_to_map().unwrap()).unwrap();
+    assert_eq!(
+        read,
+        Profile {
+            cached: false,
+            ..profile
+        }
+    );
+}
+
+#[test]
+fn defaults() {
+    let read = Profile::deserialize_owned_from_map(item([
+        ("userId", s("u1")),
+        ("n", s("Alice")),
+        ("loginCount", n("3")),
+        ("city", s("Oslo")),
+        ("code", s("ABC")),
+    ]))
+    .unwrap();
+    assert_eq!(read.score, 0);
+    assert_eq!(read.level, 7);
+    assert_eq!(read.nickname, None);
+    assert_eq!(read.labels, BTreeSet::new());
+    assert_eq!(read.address.zip, None);
+}
+
+#[test]
+fn deserialize_errors() {
+    let missing = Profile::deserialize_owned_from_map(item([("userId", s("u1"))])).unwrap_err();
+    assert_eq!(
+        missing.kind(),
+        &DeserializeErrorKind::MissingField {
+            field: "display_name".into()
+        }
+    );
+    assert_eq!(missing.item_type(), Some("Profile"));
+    assert_eq!(missing.path().to_string(), "n");
+    assert_eq!(
+        missing.to_string(),
+        "invalid Profile at n: missing required field `display_name`"
+    );
+
+    let mut attributes = profile().serialize_to_map().unwrap();
+    attributes.insert("loginCount".into(), s("3"));
+    let wrong_type = Profile::deserialize_owned_from_map(attributes).unwrap_err();
+    assert_eq!(
+        wrong_type.to_string(),
+        "invalid Profile at loginCount: expected N, found S"
+    );
+
+    let mut attributes = profile().serialize_to_map().unwrap();
+    attributes.remove("city");
+    let flattened = Profile::deserialize_owned_from_map(attributes).unwrap_err();
+    assert_eq!(
+        flattened.to_string(),
+        "invalid Profile at city: missing required field `city`"
+    );
+}
+
+#[test]
+fn hand_written_errors() {
+    let item_type = String::from("Tag");
+    let missing = DeserializeError::missing_required_field(&item_type, "name");
+    drop(item_type);
+    assert_eq!(missing.item_type(), Some("Tag"));
+    assert_eq!(
+        missing.to_string(),
+        "invalid Tag: missing required field `name`"
+    );
+}
+
+#[test]
+fn attributes() {
+    assert_eq!(
+        Profile::attributes().unwrap(),
+        [
+            "userId",
+            "n",
+            "loginCount",
+            "nickname",
+            "labels",
+            "score",
+            "level",
+            "code",
+            "city",
+            "zip"
+        ]
+    );
+}
+
+const NULLS: Policy = Policy::DEFAULT
+    .nones(Nones::Null)
+    .empty_sets(EmptySets::Null);
+
+#[derive(Debug, PartialEq, amo::Item)]
+#[amo(policy = NULLS)]
+struct WithPolicy {
+    nickname: Option<String>,
+    labels: BTreeSet<String>,
+}
+
+#[test]
+fn container_policy() {
+    let value = WithPolicy {
+        nickname: None,
+        labels: BTreeSet::new(),
+    };
+    assert_eq!(<WithPolicy as Serialize>::POLICY, NULLS);
+    assert_eq!(<Profile as Serialize>::POLICY, Policy::DEFAULT);
+    let attributes = value.serialize_to_map().unwrap();
+    assert_eq!(
+        attributes,
+        item([
+            ("nickname", AttributeValue::Null(true)),
+            ("labels", AttributeValue::Null(true))
+        ])
+    );
+    assert_eq!(
+        WithPolicy::deserialize_owned_from_map(attributes).unwrap(),
+        value
+    );
+}
+
+#[derive(Debug, amo::Item)]
+struct Overlapping {
+    city: String,
+    #[amo(flatten)]
+    address: Address,
+}
+
+#[test]
+fn flattened_attributes_must_not_overlap() {
+    let value = Overlapping {
+        city: "Oslo".into(),
+        address: Address::default(),
+    };
+    let error = value.serialize_to_map().unwrap_err();
+    assert!(matches!(error.kind(), SerializeErrorKind::Invalid(_)));
+    assert_eq!(
+        error.to_string(),
+        "at city: item has more than one attribute with this name"
+    );
+}
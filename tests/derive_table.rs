@@ -0,0 +1,222 @@
+use amo::error::{ReadError, SerializeErrorKind};
+use amo::operation::{Consistency, Query};
+use amo::table::{
+    Global, HashIndex, HashRangeIndex, HashRangeTable, HashTable, Index, IndexHandle,
+    IndexProjection, Local, Table,
+};
+use amo::value::{N, S};
+use aws_sdk_dynamodb::{config::BehaviorVersion, Client, Config};
+use futures_util::StreamExt;
+
+fn client() -> Client {
+    Client::from_conf(
+        Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .build(),
+    )
+}
+
+#[derive(Debug, Clone, amo::Item, amo::Table)]
+#[amo(rename_all = "camelCase")]
+struct Tag {
+    #[amo(primary, kind = hash)]
+    resource: String,
+    #[amo(primary, kind = range)]
+    #[amo(secondary, index = "by-account", kind = range)]
+    key: String,
+    #[amo(secondary, index = "by-account", kind = hash)]
+    #[amo(secondary, index = "accountGSI", kind = hash, projection = keys_only)]
+    account_id: String,
+    #[amo(secondary, index = "by-created", kind = range, local)]
+    created_at: u64,
+}
+
+#[derive(Debug, Clone, amo::Item, amo::Table)]
+struct User {
+    #[amo(primary, kind = hash)]
+    id: u64,
+    #[amo(secondary, index = "by-email", kind = hash)]
+    email: String,
+    #[amo(include = "by-email")]
+    name: String,
+    address: String,
+}
+
+/// The attributes of [`User`] projected into `by-email`.
+#[derive(Debug, Clone, amo::Item)]
+struct UserName {
+    id: u64,
+    email: String,
+    name: String,
+}
+
+#[derive(Debug, Clone, amo::Item, amo::Table)]
+struct Counter {
+    #[amo(primary, kind = hash)]
+    name: String,
+    count: u64,
+}
+
+fn same<T>(_: T, _: T) {}
+
+#[test]
+fn primary_keys() {
+    assert_eq!(<TagTable as HashRangeTable>::HASH_KEY_ATTRIBUTE, "resource");
+    assert_eq!(<TagTable as HashRangeTable>::RANGE_KEY_ATTRIBUTE, "key");
+    assert_eq!(<UserTable as HashTable>::HASH_KEY_ATTRIBUTE, "id");
+
+    let table = TagTable::new(client(), "tags");
+    assert_eq!(table.name(), "tags");
+    let _: Query<TagTable> = table.query().by_resource("arn:aws:s3:::abc");
+}
+
+#[test]
+fn global_indexes() {
+    assert_eq!(<TagByAccount as Index>::NAME, "by-account");
+    same(
+        std::marker::PhantomData::<<TagByAccount as Index>::Kind>,
+        std::marker::PhantomData::<Global>,
+    );
+    same(
+        std::marker::PhantomData::<<TagByAccount as HashIndex>::HashKeyType>,
+        std::marker::PhantomData::<S>,
+    );
+    assert_eq!(<TagByAccount as HashIndex>::HASH_KEY_ATTRIBUTE, "accountId");
+    assert_eq!(<TagByAccount as HashRangeIndex>::RANGE_KEY_ATTRIBUTE, "key");
+
+    // A hash-only index, named from its camel case name.
+    assert_eq!(<TagAccountGsi as Index>::NAME, "accountGSI");
+    assert_eq!(
+        <TagAccountGsi as HashIndex>::HASH_KEY_ATTRIBUTE,
+        "accountId"
+    );
+
+    let table = TagTable::new(client(), "tags");
+    let _: Query<IndexHandle<TagByAccount>> = table
+        .query()
+        .by_account("123456789012")
+        .matching_key(|key| key.begins_with("a"));
+    let _: Query<IndexHandle<TagAccountGsi>> = table.query().account_gsi("123456789012");
+
+    // Hash tables get a query builder for their indexes only.
+    let users = UserTable::new(client(), "users");
+    let _: Query<IndexHandle<UserByEmail>> = users.query().by_email("someone@example.com");
+}
+
+#[test]
+fn index_projections() {
+    assert_eq!(<TagByAccount as Index>::PROJECTION, IndexProjection::All);
+    assert_eq!(
+        <TagAccountGsi as Index>::PROJECTION,
+        IndexProjection::KeysOnly {
+            keys: &["resource", "key", "accountId"]
+        }
+    );
+    assert_eq!(
+        <UserByEmail as Index>::PROJECTION,
+        IndexProjection::Include {
+            keys: &["id", "email"],
+            attributes: &["name"]
+        }
+    );
+    assert!(<UserByEmail as Index>::PROJECTION.contains("name"));
+    assert!(!<UserByEmail as Index>::PROJECTION.contains("address"));
+}
+
+#[tokio::test]
+async fn index_reads_only_request_projected_attributes() {
+    let users = UserTable::new(client(), "users");
+
+    // `User` reads `address`, which `by-email` doesn't hold.
+    let reads = [
+        users.query().by_email("someone@example.com").next().await,
+        users.index::<UserByEmail>().all().next().await,
+    ];
+    for read in reads {
+        let error = read.unwrap().unwrap_err();
+        let ReadError::Serialize(error) = error else {
+            panic!("expected a serialize error, got {error:?}");
+        };
+        assert_eq!(
+            error.to_string(),
+            "attribute `address` isn't projected into the index `by-email`"
+        );
+    }
+
+    // Reads of projected attributes only get as far as the missing region.
+    let error = users
+        .query()
+        .by_email("someone@example.com")
+        .project::<UserName>()
+        .next()
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(matches!(error, ReadError::Network(_)), "{error:?}");
+}
+
+#[test]
+fn local_indexes() {
+    assert_eq!(<TagByCreated as Index>::NAME, "by-created");
+    same(
+        std::marker::PhantomData::<<TagByCreated as Index>::Kind>,
+        std::marker::PhantomData::<Local>,
+    );
+    assert_eq!(<TagByCreated as HashIndex>::HASH_KEY_ATTRIBUTE, "resource");
+    same(
+        std::marker::PhantomData::<<TagByCreated as HashRangeIndex>::RangeKeyType>,
+        std::marker::PhantomData::<N>,
+    );
+    assert_eq!(
+        <TagByCreated as HashRangeIndex>::RANGE_KEY_ATTRIBUTE,
+        "createdAt"
+    );
+
+    let table = TagTable::new(client(), "tags");
+    let _ = table
+        .query()
+        .by_created("arn:aws:s3:::abc")
+        .matching_key(|key| key.gt(5_u64))
+        .consistency(Consistency::Strong);
+}
+
+#[test]
+fn without_queries() {
+    let counters = CounterTable::new(client(), "counters");
+    let _ = counters.get("visits");
+}
+
+#[test]
+fn invalid_keys_fail_before_sending() {
+    let counters = CounterTable::new(client(), "counters");
+    assert!(counters.get("visits").is_ok());
+    let error = counters.delete("").unwrap_err();
+    assert_eq!(error.kind(), &SerializeErrorKind::EmptyKey);
+    assert_eq!(error.path().to_string(), "name");
+
+    let tags = TagTable::new(client(), "tags");
+    let error = tags
+        .update("arn:aws:s3:::abc", "k".repeat(1025))
+        .unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &SerializeErrorKind::KeyTooLong {
+            size: 1025,
+            limit: 1024
+        }
+    );
+    assert_eq!(error.path().to_string(), "key");
+
+    let error = tags
+        .put(Tag {
+            resource: "arn:aws:s3:::abc".into(),
+            key: "k".into(),
+            account_id: "a".repeat(400 * 1024),
+            created_at: 0,
+        })
+        .unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        SerializeErrorKind::ItemTooLarge { .. }
+    ));
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use amo::error::{ReadError, WriteError};
+use aws_sdk_dynamodb::{
+    config::http::HttpResponse,
+    error::{ErrorMetadata, SdkError},
+    operation::{
+        get_item::GetItemError, put_item::PutItemError,
+        transact_write_items::TransactWriteItemsError,
+    },
+    types::{
+        error::{ConditionalCheckFailedException, TransactionCanceledException},
+        AttributeValue, CancellationReason,
+    },
+};
+
+fn response(status: u16) -> HttpResponse {
+    HttpResponse::new(status.try_into().unwrap(), "".into())
+}
+
+fn read_error(code: &str, status: u16) -> ReadError {
+    let error = GetItemError::generic(ErrorMetadata::builder().code(code).message("no").build());
+    SdkError::service_error(error, response(status)).into()
+}
+
+#[test]
+fn service_errors_are_classified_by_code() {
+    for code in [
+        "ProvisionedThroughputExceededException",
+        "RequestLimitExceeded",
+        "ThrottlingException",
+    ] {
+        let error = read_error(code, 400);
+        assert!(matches!(error, ReadError::Throttled(_)), "{code}: {error}");
+        assert!(error.is_retryable());
+    }
+
+    let error = read_error("ResourceNotFoundException", 400);
+    assert!(matches!(error, ReadError::ResourceNotFound(_)));
+    assert!(!error.is_retryable());
+
+    let error = read_error("ValidationException", 400);
+    assert!(matches!(error, ReadError::Validation(_)));
+    assert!(!error.is_retryable());
+    assert_eq!(
+        error.to_string(),
+        "read was invalid: ValidationException: no"
+    );
+
+    let error = read_error("TransactionConflictException", 400);
+    assert!(matches!(error, ReadError::TransactionConflict(_)));
+    assert!(error.is_retryable());
+}
+
+#[test]
+fn only_server_errors_are_retryable() {
+    let error = read_error("InternalServerError", 500);
+    assert!(matches!(error, ReadError::Service(_)));
+    assert!(error.is_retryable());
+
+    let error = read_error("SomethingElse", 503);
+    assert!(matches!(error, ReadError::Service(_)));
+    assert!(error.is_retryable());
+
+    let error = read_error("SomethingElse", 400);
+    assert!(matches!(error, ReadError::Service(_)));
+    assert!(!error.is_retryable());
+    assert_eq!(error.service_error().unwrap().code(), Some("SomethingElse"));
+}
+
+#[test]
+fn requests_that_were_never_sent() {
+    let error: ReadError =
+        SdkError::<GetItemError, HttpResponse>::construction_failure("no table name").into();
+    assert!(matches!(error, ReadError::Validation(_)));
+    assert!(!error.is_retryable());
+
+    let error: ReadError = SdkError::<GetItemError, HttpResponse>::timeout_error("too slow").into();
+    assert!(matches!(error, ReadError::Network(_)));
+    assert!(error.is_retryable());
+    assert_eq!(error.request_id(), None);
+}
+
+#[test]
+fn failed_conditions_return_the_current_item() {
+    let item = HashMap::from([("id".to_owned(), AttributeValue::S("a".into()))]);
+    let exception = ConditionalCheckFailedException::builder()
+        .meta(
+            ErrorMetadata::builder()
+                .code("ConditionalCheckFailedException")
+                .build(),
+        )
+        .message("no")
+        .set_item(Some(item.clone()))
+        .build();
+    let error = PutItemError::ConditionalCheckFailedException(exception);
+    let error: WriteError = SdkError::service_error(error, response(400)).into();
+    assert!(matches!(error, WriteError::ConditionalCheckFailed(_)));
+    assert!(!error.is_retryable());
+    assert_eq!(
+        error.service_error().unwrap().current_item_raw(),
+        Some(&item)
+    );
+}
+
+#[test]
+fn canceled_transactions_are_classified_by_reason() {
+    let canceled = |codes: &[&str]| -> WriteError {
+        let exception = TransactionCanceledException::builder()
+            .set_cancellation_reasons(Some(
+                codes
+                    .iter()
+                    .map(|code| CancellationReason::builder().code(*code).build())
+                    .collect(),
+            ))
+            .build();
+        let error = TransactWriteItemsError::TransactionCanceledException(exception);
+        SdkError::service_error(error, response(400)).into()
+    };
+
+    let error = canceled(&["None", "ConditionalCheckFailed"]);
+    assert!(matches!(error, WriteError::ConditionalCheckFailed(_)));
+    let error = canceled(&["TransactionConflict", "ConditionalCheckFailed"]);
+    assert!(matches!(error, WriteError::TransactionConflict(_)));
+    let error = canceled(&["ThrottlingError"]);
+    assert!(matches!(error, WriteError::Throttled(_)));
+    let error = canceled(&["ValidationError"]);
+    assert!(matches!(error, WriteError::Validation(_)));
+    let error = canceled(&["None"]);
+    assert!(matches!(error, WriteError::Service(_)));
+}